@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::block_in_place;
+
+use crate::cards::Card;
+use crate::coordinator::{coordinator, GameSettings};
+use crate::crash::panic_message;
+use crate::game::Seat;
+use crate::game_result::GameResult;
+use crate::logger::{LogFile, info};
+use crate::reaction::Reflexes;
+use crate::signed_card::SignedCard;
+use crate::transport::{coordinator_left, BarrierState, CoordinatorTransport, GameOver, Notification, PlayerEvent, PlayerTransport, Turn};
+
+/* Motor de juego con tareas de tokio: cada jugador es una tarea en lugar de un hilo, las cartas y los
+avisos viajan por canales asincronicos y las rondas se sincronizan con una barrera asincronica. Juega
+con el mismo coordinador y el mismo jugador que el motor con hilos, solo cambia el transporte, asi que
+una misma semilla da las mismas rondas; en las rondas rusticas el orden de llegada lo decide el
+planificador de tokio. Las estrategias que bloquean (humanos, bots, jugadores remotos) se corren con
+block_in_place para no frenar al resto de las tareas. */
+pub(crate) fn run(settings: GameSettings, seats: Vec<Seat>, reflexes: Vec<Reflexes>,
+                  logfile: LogFile) -> std::io::Result<GameResult> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_time().build()?;
+    let game_result = runtime.block_on(play(settings, seats, reflexes, logfile));
+    /* No se espera a los jugadores que nunca volvieron de su ultima ronda. */
+    runtime.shutdown_background();
+    return game_result;
+}
+
+/* Barrera asincronica con el mismo estado que RoundBarrier en el motor con hilos. Cada cambio de
+estado avanza la version publicada en el canal watch, y los que esperan vuelven a mirar si ya pueden
+seguir. */
+struct AsyncRoundBarrier {
+    state: Mutex<BarrierState>,
+    version: watch::Sender<u64>
+}
+
+impl AsyncRoundBarrier {
+    fn new(participants: usize) -> Self {
+        return AsyncRoundBarrier{state: Mutex::new(BarrierState::new(participants)), version: watch::Sender::new(0)};
+    }
+
+    fn update<R, F: FnOnce(&mut BarrierState) -> R>(&self, change: F) -> R {
+        let result = change(&mut self.state.lock().unwrap());
+        self.version.send_modify(|version| *version += 1);
+        return result;
+    }
+
+    /* Espera a que ready sea cierto. Se suscribe al canal con el estado tomado para no perderse
+    ningun cambio. */
+    async fn wait_until<F: Fn(&BarrierState) -> bool>(&self, ready: F) {
+        loop {
+            let mut receiver = {
+                let state = self.state.lock().unwrap();
+                if ready(&state) {
+                    return;
+                }
+                self.version.subscribe()
+            };
+            receiver.changed().await.unwrap();
+        }
+    }
+
+    async fn wait(&self, seat: Option<i32>) {
+        let arrival = self.update(|state| state.arrive(seat));
+        self.wait_until(|state| state.is_released(&arrival)).await;
+    }
+
+    async fn readmit(&self, wait: bool) -> Vec<i32> {
+        let excused_before = self.state.lock().unwrap().excused_count();
+        if wait && excused_before > 0 {
+            self.wait_until(|state| state.can_readmit(excused_before)).await;
+        }
+        return self.update(|state| state.readmit());
+    }
+}
+
+/* Transporte entre tareas de tokio: lo mismo que InProcessCoordinator pero con canales asincronicos. */
+struct TaskCoordinator {
+    tx_hands: HashMap<i32, mpsc::UnboundedSender<Vec<Card>>>,
+    notifiers: HashMap<i32, mpsc::UnboundedSender<Notification>>,
+    card_receiver: mpsc::UnboundedReceiver<PlayerEvent>,
+    barrier: Arc<AsyncRoundBarrier>
+}
+
+struct TaskPlayer {
+    seat: i32,
+    rx_hand: mpsc::UnboundedReceiver<Vec<Card>>,
+    notifications: mpsc::UnboundedReceiver<Notification>,
+    card_sender: mpsc::UnboundedSender<PlayerEvent>,
+    barrier: Arc<AsyncRoundBarrier>
+}
+
+fn task_channels(players: i32) -> (TaskCoordinator, Vec<TaskPlayer>) {
+    let barrier = Arc::new(AsyncRoundBarrier::new((players + 1) as usize));
+    let (card_sender, card_receiver) = mpsc::unbounded_channel();
+    let mut tx_hands = HashMap::new();
+    let mut notifiers = HashMap::new();
+    let mut player_transports = Vec::new();
+    for p in 0..players {
+        let (tx_hand, rx_hand) = mpsc::unbounded_channel();
+        let (notifier, notifications) = mpsc::unbounded_channel();
+        tx_hands.insert(p, tx_hand);
+        notifiers.insert(p, notifier);
+        player_transports.push(TaskPlayer{seat: p, rx_hand, notifications, card_sender: card_sender.clone(),
+                                          barrier: barrier.clone()});
+    }
+    return (TaskCoordinator{tx_hands, notifiers, card_receiver, barrier}, player_transports);
+}
+
+/* Si el jugador ya no esta, los envios fallan y su aviso de caida llega por el canal de cartas. */
+impl CoordinatorTransport for TaskCoordinator {
+    fn deal(&mut self, player_id: i32, hand: Vec<Card>) {
+        let _ = self.tx_hands.get(&player_id).unwrap().send(hand);
+    }
+
+    fn notify_turn(&mut self, player_id: i32, turn: Turn) {
+        let _ = self.notifiers.get(&player_id).unwrap().send(Notification::Turn(turn));
+    }
+
+    async fn receive_card(&mut self) -> PlayerEvent {
+        return self.card_receiver.recv().await.unwrap();
+    }
+
+    async fn receive_card_timeout(&mut self, timeout: Duration) -> Option<PlayerEvent> {
+        return tokio::time::timeout(timeout, self.card_receiver.recv()).await.ok().flatten();
+    }
+
+    async fn sync_round(&mut self) {
+        self.barrier.wait(None).await;
+    }
+
+    fn excuse(&mut self, player_id: i32) -> bool {
+        return self.barrier.update(|state| state.excuse(player_id));
+    }
+
+    async fn readmit(&mut self, wait: bool) -> Vec<i32> {
+        return self.barrier.readmit(wait).await;
+    }
+
+    fn end_game(&mut self, player_id: i32, game_over: GameOver) {
+        let _ = self.notifiers.get(&player_id).unwrap().send(Notification::GameOver(game_over));
+    }
+}
+
+impl Drop for TaskCoordinator {
+    fn drop(&mut self) {
+        self.barrier.update(|state| state.close());
+    }
+}
+
+impl PlayerTransport for TaskPlayer {
+    async fn receive_hand(&mut self) -> std::io::Result<Vec<Card>> {
+        return self.rx_hand.recv().await.ok_or_else(|| coordinator_left(()));
+    }
+
+    async fn wait_turn(&mut self) -> std::io::Result<Notification> {
+        return self.notifications.recv().await.ok_or_else(|| coordinator_left(()));
+    }
+
+    fn send_card(&mut self, signed_card: SignedCard) -> std::io::Result<()> {
+        let _ = self.card_sender.send(PlayerEvent::Card(signed_card));
+        return Ok(());
+    }
+
+    async fn sync_round(&mut self) -> std::io::Result<()> {
+        self.barrier.wait(Some(self.seat)).await;
+        return Ok(());
+    }
+
+    async fn sleep(&mut self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+
+    fn block<R, F: FnOnce() -> R>(&mut self, call: F) -> R {
+        return block_in_place(call);
+    }
+}
+
+async fn play(settings: GameSettings, seats: Vec<Seat>, reflexes: Vec<Reflexes>,
+              logfile: LogFile) -> std::io::Result<GameResult> {
+    let (coordinator_transport, player_transports) = task_channels(settings.players);
+    let barrier = coordinator_transport.barrier.clone();
+
+    let mut tasks = Vec::new();
+
+    let seats = seats.into_iter().zip(reflexes);
+    for (p, (transport, (seat, reflexes))) in player_transports.into_iter().zip(seats).enumerate() {
+        let p = p as i32;
+        /* Si la tarea del jugador entra en panico o termina con un error, se la saca de la barrera y
+        se le avisa al coordinador. */
+        let (crash_sender, crash_barrier) = (transport.card_sender.clone(), transport.barrier.clone());
+        let task = tokio::spawn(seat.play(logfile.clone(), transport, p, reflexes));
+        tasks.push(tokio::spawn(async move {
+            let reason = match task.await {
+                Ok(Ok(())) => return,
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => match e.try_into_panic() {
+                    Ok(payload) => panic_message(payload.as_ref()),
+                    Err(e) => e.to_string()
+                }
+            };
+            let _ = crash_sender.send(PlayerEvent::Crashed{player_id: p, reason});
+            crash_barrier.update(|state| state.leave(p));
+        }));
+    }
+
+    info(logfile.clone(), "Iniciando coordinador".to_string())?;
+    let game_result = coordinator(logfile, settings, coordinator_transport).await;
+
+    for (p, task) in tasks.into_iter().enumerate() {
+        if barrier.state.lock().unwrap().is_excused(p as i32) {
+            continue;
+        }
+        task.await.map_err(|_| std::io::Error::other("A player supervisor task panicked."))?;
+    }
+
+    return game_result;
+}
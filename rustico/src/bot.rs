@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cards::Card;
+use crate::game_result::{RoundResult, RoundType};
+use crate::logger::{LogFile, debug, error};
+use crate::round_scorer::Points;
+use crate::signed_card::SignedCard;
+use crate::strategy::{PlayerStrategy, TableView};
+
+pub const BOT_TIMEOUT_MS :u64 = 1000;
+
+/* Mensajes del protocolo de bots externos, descripto en BOT_PROTOCOL.md. Cada mensaje es una linea
+con el nombre del mensaje seguido de campos clave=valor; las listas van separadas por comas. */
+
+pub fn card_token(card: &Card) -> String {
+    return format!("{}:{}", card.number, card.suit.name().to_lowercase());
+}
+
+pub fn parse_card_token(token: &str) -> Option<Card> {
+    let (number, suit) = token.split_once(':')?;
+    return Some(Card{number: number.parse().ok()?, suit: suit.parse().ok()?});
+}
+
+fn list<T, F: Fn(&T) -> String>(items: &[T], format_item: F) -> String {
+    return items.iter().map(format_item).collect::<Vec<String>>().join(",");
+}
+
+fn signed_cards(cards: &[SignedCard]) -> String {
+    return list(cards, |signed_card| format!("{}:{}", signed_card.player_signature, card_token(&signed_card.card)));
+}
+
+fn points_by_seat(points_by_user: &HashMap<i32, Points>) -> String {
+    let mut points: Vec<(&i32, &Points)> = points_by_user.iter().collect();
+    points.sort_by_key(|(seat, _)| **seat);
+    return list(&points, |(seat, points)| format!("{}:{}", seat, points));
+}
+
+fn seats(seats: &[i32]) -> String {
+    return list(seats, |seat| seat.to_string());
+}
+
+fn optional_seat(seat: Option<i32>) -> String {
+    return seat.map_or("-".to_string(), |seat| seat.to_string());
+}
+
+fn round_type(round_type: RoundType) -> &'static str {
+    return match round_type {
+        RoundType::Normal => "normal",
+        RoundType::Rustic => "rustic"
+    };
+}
+
+pub fn deal_message(seat: i32, hand: &[Card]) -> String {
+    return format!("deal seat={} hand={}", seat, list(hand, card_token));
+}
+
+pub fn your_turn_message(hand: &[Card], table: &TableView) -> String {
+    return format!("your-turn round={} type={} hand={} played={} points={} suspended={}",
+                   table.round, round_type(table.round_type), list(hand, card_token),
+                   signed_cards(&table.cards_played), points_by_seat(&table.points_by_user),
+                   seats(&table.suspended_players));
+}
+
+pub fn round_result_message(round: &RoundResult) -> String {
+    let outcome = &round.hand_outcome;
+    let mut max_card: Vec<i32> = outcome.players_with_max_card.iter().copied().collect();
+    max_card.sort();
+    return format!("round-result round={} type={} cards={} earned={} max-card={} fastest={} slowest={} suspended={}",
+                   round.round, round_type(round.round_type), signed_cards(&round.cards),
+                   points_by_seat(&outcome.earned_points), seats(&max_card),
+                   optional_seat(outcome.fastest_player), optional_seat(outcome.slowest_player),
+                   seats(&round.suspended_players));
+}
+
+pub fn game_over_message(points_by_user: &HashMap<i32, Points>, winners: &[i32]) -> String {
+    return format!("game-over points={} winners={}", points_by_seat(points_by_user), seats(winners));
+}
+
+/* Asiento jugado por un programa externo. Si el bot no responde a tiempo, termina o se cae la
+comunicacion, el asiento sigue jugando en orden de reparto hasta el final de la partida; si responde
+con una carta que no tiene, se tira la primera de la mano solo en ese turno. */
+pub struct BotStrategy {
+    seat: i32,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    replies: Receiver<String>,
+    timeout: Duration,
+    logfile: LogFile
+}
+
+impl BotStrategy {
+    pub fn spawn(command: &str, timeout: Duration, logfile: LogFile) -> std::io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next()
+            .ok_or_else(|| std::io::Error::other("The bot command is empty."))?;
+        return BotStrategy::spawn_command(Command::new(program).args(parts), timeout, logfile);
+    }
+
+    pub fn spawn_command(command: &mut Command, timeout: Duration, logfile: LogFile) -> std::io::Result<Self> {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().unwrap();
+        let (tx_replies, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if tx_replies.send(line).is_err() { return; },
+                    Err(_) => return
+                }
+            }
+        });
+        return Ok(BotStrategy{seat: -1, child, stdin, replies, timeout, logfile});
+    }
+
+    fn is_alive(&self) -> bool {
+        return self.stdin.is_some();
+    }
+
+    fn fail(&mut self, reason: String) {
+        let _ = error(self.logfile.clone(), format!("El bot del jugador {} {}: juega en orden de reparto el resto \
+                                                     de la partida.", self.seat, reason));
+        self.stdin = None;
+        let _ = self.child.kill();
+    }
+
+    fn send(&mut self, message: String) {
+        if let Some(stdin) = self.stdin.as_mut() {
+            if writeln!(stdin, "{}", message).and_then(|_| stdin.flush()).is_err() {
+                self.fail("no acepta mensajes".to_string());
+            }
+        }
+    }
+
+    /* Descarta lo que el bot haya escrito fuera de turno, para que no se confunda con la respuesta. */
+    fn discard_pending_replies(&mut self) {
+        while let Ok(line) = self.replies.try_recv() {
+            let _ = debug(self.logfile.clone(), format!("Se ignora la linea '{}' del bot del jugador {}.", line, self.seat));
+        }
+    }
+}
+
+impl PlayerStrategy for BotStrategy {
+    fn choose_card(&mut self, hand: &[Card], table: &TableView) -> usize {
+        self.discard_pending_replies();
+        self.send(your_turn_message(hand, table));
+        if !self.is_alive() {
+            return 0;
+        }
+        let reply = match self.replies.recv_timeout(self.timeout) {
+            Ok(reply) => reply,
+            Err(RecvTimeoutError::Timeout) => {
+                self.fail(format!("no respondio en {} ms", self.timeout.as_millis()));
+                return 0;
+            },
+            Err(RecvTimeoutError::Disconnected) => {
+                self.fail("termino".to_string());
+                return 0;
+            }
+        };
+        let card = reply.strip_prefix("throw ").and_then(|token| parse_card_token(token.trim()));
+        return match card.and_then(|card| hand.iter().position(|in_hand| *in_hand == card)) {
+            Some(i) => i,
+            None => {
+                let _ = error(self.logfile.clone(), format!("El bot del jugador {} respondio '{}', que no es una carta \
+                                                             de su mano: se tira la primera.", self.seat, reply));
+                0
+            }
+        };
+    }
+
+    fn deal(&mut self, player_id: i32, hand: &[Card]) {
+        self.seat = player_id;
+        self.send(deal_message(player_id, hand));
+    }
+
+    fn round_result(&mut self, round: &RoundResult) {
+        self.send(round_result_message(round));
+    }
+
+    fn game_over(&mut self, points_by_user: &HashMap<i32, Points>, winners: &[i32]) {
+        self.send(game_over_message(points_by_user, winners));
+        /* Cerrar la entrada le avisa al bot que no hay mas mensajes. */
+        self.stdin = None;
+    }
+
+    fn description(&self) -> String {
+        return "bot externo".to_string();
+    }
+}
+
+/* Se le da al bot el mismo tiempo que tiene para responder un turno para que termine por su cuenta. */
+impl Drop for BotStrategy {
+    fn drop(&mut self) {
+        self.stdin = None;
+        let start = Instant::now();
+        while start.elapsed() < self.timeout {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::cards::CardSuit;
+    use crate::game::Game;
+
+    /* Bot de prueba: responde cada turno con la ultima carta de la mano. */
+    const LAST_CARD_BOT: &str = r#"while read -r message rest; do
+        case "$message" in
+            your-turn) hand=${rest#*hand=}; hand=${hand%% *}; echo "throw ${hand##*,}";;
+        esac
+    done"#;
+
+    fn sh_bot(script: &str, timeout_ms: u64) -> BotStrategy {
+        return BotStrategy::spawn_command(Command::new("sh").args(["-c", script]),
+                                          Duration::from_millis(timeout_ms), Arc::new(Mutex::new(None))).unwrap();
+    }
+
+    fn hand() -> Vec<Card> {
+        return vec![Card{number: 5, suit: CardSuit::Spades}, Card{number: 12, suit: CardSuit::Hearts}];
+    }
+
+    #[test]
+    fn test_card_tokens() {
+        let card = Card{number: 10, suit: CardSuit::Hearts};
+        assert_eq!(card_token(&card), "10:hearts");
+        assert_eq!(parse_card_token("10:hearts"), Some(card));
+        assert_eq!(parse_card_token("10"), None);
+        assert_eq!(parse_card_token("ten:hearts"), None);
+    }
+
+    #[test]
+    fn test_messages() {
+        let mut points_by_user = HashMap::new();
+        points_by_user.insert(1, Points::new(7, 2));
+        points_by_user.insert(0, Points::from_integer(-5));
+        let table = TableView{round: 4, round_type: RoundType::Normal, points_by_user: points_by_user.clone(),
+                              cards_played: vec![SignedCard{card: hand()[0], player_signature: 0}],
+                              ..TableView::default()};
+        assert_eq!(your_turn_message(&hand(), &table),
+                   "your-turn round=4 type=normal hand=5:spades,12:hearts played=0:5:spades points=0:-5,1:7/2 suspended=");
+        assert_eq!(game_over_message(&points_by_user, &[1]), "game-over points=0:-5,1:7/2 winners=1");
+        assert_eq!(deal_message(2, &hand()), "deal seat=2 hand=5:spades,12:hearts");
+    }
+
+    #[test]
+    fn test_bot_chooses_card() {
+        let mut bot = sh_bot(LAST_CARD_BOT, 2000);
+        bot.deal(0, &hand());
+        assert_eq!(bot.choose_card(&hand(), &TableView::default()), 1);
+        assert_eq!(bot.choose_card(&hand()[..1], &TableView::default()), 0);
+        assert!(bot.is_alive());
+    }
+
+    #[test]
+    fn test_invalid_reply_throws_first_card() {
+        let mut bot = sh_bot("while read -r line; do echo 'throw 1:oros'; done", 2000);
+        assert_eq!(bot.choose_card(&hand(), &TableView::default()), 0);
+        assert!(bot.is_alive());
+    }
+
+    #[test]
+    fn test_silent_bot_times_out() {
+        let mut bot = sh_bot("cat > /dev/null", 100);
+        assert_eq!(bot.choose_card(&hand(), &TableView::default()), 0);
+        assert!(!bot.is_alive());
+        assert_eq!(bot.choose_card(&hand(), &TableView::default()), 0);
+    }
+
+    #[test]
+    fn test_exited_bot_is_replaced() {
+        let mut bot = sh_bot("exit 0", 2000);
+        assert_eq!(bot.choose_card(&hand(), &TableView::default()), 0);
+        assert!(!bot.is_alive());
+    }
+
+    #[test]
+    fn test_game_with_bot_seat() {
+        let result = Game::builder(4).seed(5).custom_strategy(3, Box::new(sh_bot(LAST_CARD_BOT, 2000)))
+            .build().unwrap().run().unwrap();
+        assert_eq!(result.points_by_user.len(), 4);
+    }
+}
@@ -1,43 +1,317 @@
 extern crate rand;
+use rand::Rng;
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum CardSuit {
     Spades,
     Hearts,
     Diamonds,
-    Clubs
+    Clubs,
+    Spanish(SpanishSuit),
+    Joker
 }
 
-#[derive(Copy, Clone)]
+/* Palos de la baraja espanola. */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SpanishSuit {
+    Oros,
+    Copas,
+    Espadas,
+    Bastos
+}
+
+impl CardSuit {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            CardSuit::Spades => "Spades",
+            CardSuit::Hearts => "Hearts",
+            CardSuit::Diamonds => "Diamonds",
+            CardSuit::Clubs => "Clubs",
+            CardSuit::Spanish(SpanishSuit::Oros) => "Oros",
+            CardSuit::Spanish(SpanishSuit::Copas) => "Copas",
+            CardSuit::Spanish(SpanishSuit::Espadas) => "Espadas",
+            CardSuit::Spanish(SpanishSuit::Bastos) => "Bastos",
+            CardSuit::Joker => "Joker"
+        };
+    }
+}
+
+impl std::str::FromStr for CardSuit {
+    type Err = String;
+
+    fn from_str(suit: &str) -> Result<Self, Self::Err> {
+        return match suit.trim().to_lowercase().as_str() {
+            "spades" => Ok(CardSuit::Spades),
+            "hearts" => Ok(CardSuit::Hearts),
+            "diamonds" => Ok(CardSuit::Diamonds),
+            "clubs" => Ok(CardSuit::Clubs),
+            "oros" => Ok(CardSuit::Spanish(SpanishSuit::Oros)),
+            "copas" => Ok(CardSuit::Spanish(SpanishSuit::Copas)),
+            "espadas" => Ok(CardSuit::Spanish(SpanishSuit::Espadas)),
+            "bastos" => Ok(CardSuit::Spanish(SpanishSuit::Bastos)),
+            "joker" => Ok(CardSuit::Joker),
+            _ => Err(format!("Unknown suit '{}', expected spades, hearts, diamonds, clubs, oros, copas, \
+                              espadas, bastos or joker.", suit))
+        };
+    }
+}
+
+impl From<CardSuit> for String {
+    fn from(suit: CardSuit) -> Self {
+        return suit.name().to_string();
+    }
+}
+
+impl TryFrom<String> for CardSuit {
+    type Error = String;
+
+    fn try_from(suit: String) -> Result<Self, Self::Error> {
+        return suit.parse();
+    }
+}
+
+/* Orden de los numeros de las cartas. Con AceLow el as (1) es la carta mas baja, con AceHigh queda
+por encima del rey. El comodin le gana a cualquier carta en los dos ordenes. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankOrder {
+    #[default]
+    AceLow,
+    AceHigh
+}
+
+impl RankOrder {
+    pub fn rank(&self, card: &Card) -> i32 {
+        return match self {
+            _ if card.suit == CardSuit::Joker => 15,
+            RankOrder::AceHigh if card.number == 1 => 14,
+            _ => card.number
+        };
+    }
+
+    pub fn description(&self) -> &'static str {
+        return match self {
+            RankOrder::AceLow => "as bajo",
+            RankOrder::AceHigh => "as alto"
+        };
+    }
+}
+
+impl std::str::FromStr for RankOrder {
+    type Err = String;
+
+    fn from_str(order: &str) -> Result<Self, Self::Err> {
+        return match order.trim().to_lowercase().replace('-', "_").as_str() {
+            "ace_low" => Ok(RankOrder::AceLow),
+            "ace_high" => Ok(RankOrder::AceHigh),
+            _ => Err(format!("Unknown rank order '{}', expected ace_low or ace_high.", order))
+        };
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Card {
     pub number: i32,
     pub suit: CardSuit
 }
 
-pub fn random_full_deck() -> Vec<Card>{
-    let mut full_deck = Vec::new();
-    let mut rng = &mut rand::thread_rng();
-    let suits = [CardSuit::Spades, CardSuit::Hearts,
-                CardSuit::Diamonds, CardSuit::Clubs];
-    for suit in suits.iter(){
-        for i in 1..14{
-            full_deck.push(Card{number: i, suit: *suit});
+/* Composicion de un mazo: el frances de 52 cartas o el espanol de 40 (sin ochos ni nueves) o 48. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeckKind {
+    #[default]
+    French,
+    Spanish40,
+    Spanish48
+}
+
+impl DeckKind {
+    pub fn suits(&self) -> [CardSuit; 4] {
+        return match self {
+            DeckKind::French => [CardSuit::Spades, CardSuit::Hearts, CardSuit::Diamonds, CardSuit::Clubs],
+            DeckKind::Spanish40 | DeckKind::Spanish48 => [CardSuit::Spanish(SpanishSuit::Oros),
+                                                           CardSuit::Spanish(SpanishSuit::Copas),
+                                                           CardSuit::Spanish(SpanishSuit::Espadas),
+                                                           CardSuit::Spanish(SpanishSuit::Bastos)]
+        };
+    }
+
+    pub fn numbers(&self) -> Vec<i32> {
+        return match self {
+            DeckKind::French => (1..14).collect(),
+            DeckKind::Spanish40 => (1..8).chain(10..13).collect(),
+            DeckKind::Spanish48 => (1..13).collect()
+        };
+    }
+
+    pub fn description(&self) -> &'static str {
+        return match self {
+            DeckKind::French => "frances",
+            DeckKind::Spanish40 => "espanol de 40 cartas",
+            DeckKind::Spanish48 => "espanol de 48 cartas"
+        };
+    }
+}
+
+impl std::str::FromStr for DeckKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        return match kind.trim().to_lowercase().replace('-', "_").as_str() {
+            "french" => Ok(DeckKind::French),
+            "spanish40" | "spanish_40" => Ok(DeckKind::Spanish40),
+            "spanish48" | "spanish_48" => Ok(DeckKind::Spanish48),
+            _ => Err(format!("Unknown deck '{}', expected french, spanish40 or spanish48.", kind))
+        };
+    }
+}
+
+/* Arma el zapato con el que se reparte: decks mazos del tipo kind, cada uno con jokers comodines. */
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeckBuilder {
+    pub kind: DeckKind,
+    pub decks: i32,
+    pub jokers: i32
+}
+
+impl Default for DeckBuilder {
+    fn default() -> Self {
+        return DeckBuilder{kind: DeckKind::French, decks: 1, jokers: 0};
+    }
+}
+
+impl DeckBuilder {
+    pub fn new(kind: DeckKind) -> Self {
+        return DeckBuilder{kind, ..DeckBuilder::default()};
+    }
+
+    pub fn decks(mut self, decks: i32) -> Self {
+        self.decks = decks;
+        return self;
+    }
+
+    pub fn jokers(mut self, jokers: i32) -> Self {
+        self.jokers = jokers;
+        return self;
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.decks < 1 {
+            return Err("The shoe should have at least one deck.".to_string());
+        }
+        if self.jokers < 0 {
+            return Err("Jokers per deck can not be negative.".to_string());
+        }
+        return Ok(());
+    }
+
+    fn deck_size(&self) -> i32 {
+        return self.kind.numbers().len() as i32 * 4 + self.jokers;
+    }
+
+    pub fn size(&self) -> i32 {
+        return self.decks * self.deck_size();
+    }
+
+    /* Agrega los mazos que hagan falta para que cada uno de los jugadores reciba al menos una carta. */
+    pub fn for_players(&self, players: i32) -> DeckBuilder {
+        let needed = (players + self.deck_size() - 1) / self.deck_size();
+        return self.decks(self.decks.max(needed));
+    }
+
+    pub fn build<R: Rng>(&self, rng: &mut R) -> Vec<Card> {
+        let mut full_deck = Vec::new();
+        for _ in 0..self.decks {
+            for suit in self.kind.suits().iter(){
+                for i in self.kind.numbers(){
+                    full_deck.push(Card{number: i, suit: *suit});
+                }
+            }
+            for _ in 0..self.jokers {
+                full_deck.push(Card{number: 0, suit: CardSuit::Joker});
+            }
         }
+        //Mix the deck
+        full_deck.shuffle(rng);
+        return full_deck;
     }
-    //Mix the deck
-    full_deck = full_deck.choose_multiple(&mut rng, full_deck.len()).map(|x| *x).collect();
-    return full_deck;
+}
+
+pub fn random_full_deck<R: Rng>(rng: &mut R) -> Vec<Card>{
+    return DeckBuilder::default().build(rng);
 }
 
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
 
     #[test]
     fn test_empty_points_map_len() {
-        let deck = random_full_deck();
+        let deck = random_full_deck(&mut rand::thread_rng());
         assert_eq!(deck.len(), 52);
     }
+
+    #[test]
+    fn test_same_seed_same_deck() {
+        let deck1 = random_full_deck(&mut StdRng::seed_from_u64(42));
+        let deck2 = random_full_deck(&mut StdRng::seed_from_u64(42));
+        assert_eq!(deck1, deck2);
+    }
+
+    #[test]
+    fn test_shoe_has_enough_cards_for_every_player() {
+        let deck = DeckBuilder::default();
+        assert_eq!(deck.for_players(2).decks, 1);
+        assert_eq!(deck.for_players(52).decks, 1);
+        assert_eq!(deck.for_players(53).decks, 2);
+        assert_eq!(deck.decks(4).for_players(105).decks, 4);
+        let shoe = deck.decks(2).build(&mut StdRng::seed_from_u64(42));
+        assert_eq!(shoe.len(), 104);
+        assert_eq!(shoe.iter().filter(|card| card.number == 1 && card.suit == CardSuit::Spades).count(), 2);
+    }
+
+    #[test]
+    fn test_spanish_decks() {
+        let deck = DeckBuilder::new(DeckKind::Spanish40).build(&mut StdRng::seed_from_u64(42));
+        assert_eq!(deck.len(), 40);
+        assert!(deck.iter().all(|card| card.number != 8 && card.number != 9));
+        assert_eq!(deck.iter().filter(|card| card.suit == CardSuit::Spanish(SpanishSuit::Oros)).count(), 10);
+        let deck = DeckBuilder::new(DeckKind::Spanish48).decks(2).build(&mut StdRng::seed_from_u64(42));
+        assert_eq!(deck.len(), 96);
+        assert_eq!(deck.iter().filter(|card| card.number == 12).count(), 8);
+    }
+
+    #[test]
+    fn test_jokers_are_added_to_every_deck() {
+        let deck = DeckBuilder::default().decks(2).jokers(2);
+        assert_eq!(deck.size(), 108);
+        let shoe = deck.build(&mut StdRng::seed_from_u64(42));
+        assert_eq!(shoe.iter().filter(|card| card.suit == CardSuit::Joker).count(), 4);
+    }
+
+    #[test]
+    fn test_rank_order() {
+        let ace = Card{number: 1, suit: CardSuit::Hearts};
+        let king = Card{number: 13, suit: CardSuit::Hearts};
+        let joker = Card{number: 0, suit: CardSuit::Joker};
+        assert!(RankOrder::AceLow.rank(&ace) < RankOrder::AceLow.rank(&king));
+        assert!(RankOrder::AceHigh.rank(&ace) > RankOrder::AceHigh.rank(&king));
+        assert_eq!(RankOrder::AceHigh.rank(&king), 13);
+        assert!(RankOrder::AceHigh.rank(&joker) > RankOrder::AceHigh.rank(&ace));
+    }
+
+    #[test]
+    fn test_parse_suit() {
+        assert_eq!("Oros".parse::<CardSuit>(), Ok(CardSuit::Spanish(SpanishSuit::Oros)));
+        assert_eq!("hearts".parse::<CardSuit>(), Ok(CardSuit::Hearts));
+        assert!("swords".parse::<CardSuit>().is_err());
+    }
 }
@@ -0,0 +1,180 @@
+use std::fs;
+use serde::Deserialize;
+
+use crate::logger::LogLevel;
+use crate::ruleset::Ruleset;
+use crate::game::{validate_game, Engine};
+use crate::strategy::StrategyKind;
+use crate::sequential::ArrivalModel;
+use crate::reaction::ReactionTime;
+use crate::crash::CrashPolicy;
+
+/* Configuracion de una partida leida de un archivo TOML. Todos los campos son opcionales: los que
+no aparecen toman su valor por defecto o el que se indique por linea de comandos. */
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub players: Option<i32>,
+    pub seed: Option<u64>,
+    pub log_level: LogLevel,
+    /* Estrategia de todos los asientos, salvo los primeros que se indiquen en seat_strategies. */
+    pub strategy: StrategyKind,
+    pub seat_strategies: Vec<StrategyKind>,
+    /* Tiempo de reaccion en las rondas rusticas de todos los asientos, salvo los primeros que se
+    indiquen en seat_reaction_times. */
+    pub reaction_time: ReactionTime,
+    pub seat_reaction_times: Vec<ReactionTime>,
+    pub engine: Engine,
+    /* Orden de llegada de las rondas rusticas con el motor secuencial. */
+    pub arrival: ArrivalModel,
+    /* Que hacer si un jugador entra en panico. */
+    pub on_crash: CrashPolicy,
+    pub rules: Ruleset,
+    pub output: OutputConfig
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub debug_log: Option<String>,
+    pub json: Option<String>
+}
+
+pub fn parse_config(content: &str) -> Result<GameConfig, String> {
+    return toml::from_str(content).map_err(|e| e.to_string());
+}
+
+pub fn load_config(path: &str) -> Result<GameConfig, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read config file {}: {}", path, e))?;
+    return parse_config(&content).map_err(|e| format!("Invalid config file {}: {}", path, e));
+}
+
+impl GameConfig {
+    pub fn validate(&self) -> Result<i32, String> {
+        let players = match self.players {
+            Some(players) => players,
+            None => return Err("Number of players should be given with --players or in the config file.".to_string())
+        };
+        validate_game(players, &self.rules)?;
+        if self.seat_strategies.len() > players as usize {
+            return Err(format!("{} seat strategies given for {} players.", self.seat_strategies.len(), players));
+        }
+        if self.seat_reaction_times.len() > players as usize {
+            return Err(format!("{} seat reaction times given for {} players.", self.seat_reaction_times.len(), players));
+        }
+        return Ok(players);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiebreak::TiebreakPolicy;
+    use crate::round_scorer::MaxCardSplit;
+    use crate::coordinator::{LatePolicy, LeftoverPolicy};
+    use crate::cards::{CardSuit, DeckBuilder, DeckKind, RankOrder, SpanishSuit};
+
+    #[test]
+    fn test_parse_full_config() {
+        let config = parse_config(r#"
+            players = 6
+            seed = 42
+            log_level = "info"
+            strategy = "random"
+            seat_strategies = ["highest_first", "save_high_cards"]
+            engine = "sequential"
+            arrival = "seat_order"
+            on_crash = "replace"
+            reaction_time = "normal:250,40"
+            seat_reaction_times = ["fixed:100"]
+
+            [rules]
+            points_faster_player = 2
+            points_max_card = 12
+            max_card_split = "carry_remainder"
+            normal_round_probability = 0.75
+            tiebreak = "most_max_cards"
+            rank_order = "ace_high"
+            leftover = "kitty"
+            points_kitty_suit = 2
+            rustic_deadline_ms = 500
+            late_policy = "forfeit"
+            suit_precedence = ["hearts", "spades", "diamonds", "clubs"]
+
+            [rules.deck]
+            decks = 2
+            jokers = 1
+
+            [output]
+            debug_log = "game.log"
+            json = "result.json"
+        "#).unwrap();
+        assert_eq!(config.players, Some(6));
+        assert_eq!(config.seed, Some(42));
+        assert_eq!(config.log_level, LogLevel::Info);
+        assert_eq!(config.strategy, StrategyKind::Random);
+        assert_eq!(config.seat_strategies, vec![StrategyKind::HighestFirst, StrategyKind::SaveHighCards]);
+        assert_eq!(config.engine, Engine::Sequential);
+        assert_eq!(config.arrival, ArrivalModel::SeatOrder);
+        assert_eq!(config.on_crash, CrashPolicy::Replace);
+        assert_eq!(config.reaction_time, ReactionTime::Normal{mean_ms: 250.0, std_dev_ms: 40.0});
+        assert_eq!(config.seat_reaction_times, vec![ReactionTime::Fixed{ms: 100.0}]);
+        assert_eq!(config.rules.points_faster_player, 2);
+        assert_eq!(config.rules.points_slower_player, Ruleset::default().points_slower_player);
+        assert_eq!(config.rules.points_max_card, 12);
+        assert_eq!(config.rules.max_card_split, MaxCardSplit::CarryRemainder);
+        assert_eq!(config.rules.normal_round_probability, 0.75);
+        assert_eq!(config.rules.tiebreak, TiebreakPolicy::MostMaxCards);
+        assert_eq!(config.rules.rank_order, RankOrder::AceHigh);
+        assert_eq!(config.rules.leftover, LeftoverPolicy::Kitty);
+        assert_eq!(config.rules.points_kitty_suit, 2);
+        assert_eq!(config.rules.rustic_deadline_ms, Some(500));
+        assert_eq!(config.rules.late_policy, LatePolicy::Forfeit);
+        assert_eq!(config.rules.suit_precedence,
+                   Some([CardSuit::Hearts, CardSuit::Spades, CardSuit::Diamonds, CardSuit::Clubs]));
+        assert_eq!(config.rules.deck, DeckBuilder::default().decks(2).jokers(1));
+        assert_eq!(config.output.debug_log, Some("game.log".to_string()));
+        assert_eq!(config.output.json, Some("result.json".to_string()));
+        assert_eq!(config.validate(), Ok(6));
+    }
+
+    #[test]
+    fn test_parse_spanish_deck() {
+        let config = parse_config(r#"
+            players = 4
+
+            [rules]
+            suit_precedence = ["oros", "copas", "espadas", "bastos"]
+
+            [rules.deck]
+            kind = "spanish48"
+        "#).unwrap();
+        assert_eq!(config.rules.deck.kind, DeckKind::Spanish48);
+        assert_eq!(config.rules.suit_precedence.unwrap()[0], CardSuit::Spanish(SpanishSuit::Oros));
+        assert_eq!(config.validate(), Ok(4));
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = parse_config("").unwrap();
+        assert_eq!(config, GameConfig::default());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        assert!(parse_config("player = 4").is_err());
+        assert!(parse_config("[rules]\npoints_max = 4").is_err());
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        assert!(parse_config("players = \"four\"").is_err());
+        assert!(parse_config("log_level = \"verbose\"").is_err());
+        assert!(parse_config("players = 1").unwrap().validate().is_err());
+        assert!(parse_config("players = 4\n[rules]\nsuspension_rounds = 4").unwrap().validate().is_err());
+        assert!(parse_config("players = 2\nseat_strategies = [\"random\", \"random\", \"random\"]").unwrap()
+            .validate().is_err());
+    }
+}
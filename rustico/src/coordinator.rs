@@ -1,44 +1,145 @@
-use std::sync::mpsc::{Receiver, Sender};
-use std::sync::{Barrier, Arc, Mutex, Condvar};
-use std::collections::{HashMap, HashSet};
-use rand::Rng;
+use std::collections::HashMap;
+use std::ops::Add;
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
 
 use crate::signed_card::SignedCard;
-use crate::cards::{Card, random_full_deck};
-use crate::player::RoundPlayerFlags;
+use crate::cards::{Card, CardSuit, DeckBuilder};
+use crate::strategy::TableView;
+use crate::transport::{CoordinatorTransport, GameOver, PlayerEvent, Turn};
+use crate::round_scorer::{empty_hand_outcome, HandOutcome, RoundScorers, Points, points};
+use crate::ruleset::Ruleset;
+use crate::game_result::{GameResult, RoundResult, RoundType};
+use crate::tiebreak::{break_tie, players_with_max_points};
+use crate::crash::CrashPolicy;
+use crate::game::MIN_PLAYERS;
+
+use crate::logger::{LogFile, info, debug, error};
+
+pub struct GameSettings {
+    pub players: i32,
+    pub seed: u64,
+    pub ruleset: Ruleset,
+    pub scorers: RoundScorers,
+    pub crash_policy: CrashPolicy
+}
+
+/* Que se hace con las cartas que sobran al repartir. Kitty da vuelta la primera y su palo otorga
+puntos extra durante toda la partida, DrawPile las deja en un pozo del que roba el mas rapido de cada
+ronda rustica y TiebreakDeck las guarda para la muerte subita del desempate. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeftoverPolicy {
+    #[default]
+    Discard,
+    Kitty,
+    DrawPile,
+    TiebreakDeck
+}
+
+impl LeftoverPolicy {
+    pub fn description(&self) -> &'static str {
+        return match self {
+            LeftoverPolicy::Discard => "se descartan",
+            LeftoverPolicy::Kitty => "carta boca arriba con palo bonificado",
+            LeftoverPolicy::DrawPile => "pozo para el mas rapido",
+            LeftoverPolicy::TiebreakDeck => "mazo de muerte subita"
+        };
+    }
+}
+
+impl std::str::FromStr for LeftoverPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        return match policy.trim().to_lowercase().replace('-', "_").as_str() {
+            "discard" => Ok(LeftoverPolicy::Discard),
+            "kitty" => Ok(LeftoverPolicy::Kitty),
+            "draw_pile" => Ok(LeftoverPolicy::DrawPile),
+            "tiebreak_deck" => Ok(LeftoverPolicy::TiebreakDeck),
+            _ => Err(format!("Unknown leftover policy '{}', expected discard, kitty, draw_pile or tiebreak_deck.", policy))
+        };
+    }
+}
 
-use crate::logger::{LogFile, info, debug};
+/* Que le pasa a quien no tira su carta antes del tiempo limite de una ronda rustica. Slowest lo
+cuenta como el mas lento de la ronda (resta los puntos y queda suspendido) y Forfeit solo le hace
+perder la carta. En ambos casos la carta no se puntua. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatePolicy {
+    #[default]
+    Slowest,
+    Forfeit
+}
 
-const FRENCH_DECK_SIZE :i32 = 52;
+impl LatePolicy {
+    pub fn description(&self) -> &'static str {
+        return match self {
+            LatePolicy::Slowest => "cuenta como el mas lento",
+            LatePolicy::Forfeit => "pierde la carta"
+        };
+    }
+}
 
-const POINTS_FASTER_PLAYER :i32 = 1;
-const POINTS_SLOWER_PLAYER :i32 = -5;
-const POINTS_MAX_CARD :i32 = 10;
+impl std::str::FromStr for LatePolicy {
+    type Err = String;
 
-struct HandOutcome {
-    pub earned_points: HashMap<i32, i32>,
-    pub max_card_points: i32,
-    pub players_with_max_card: HashSet<i32>,
-    pub slowest_player: Option<i32>,
-    pub fastest_player: Option<i32>
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        return match policy.trim().to_lowercase().replace('-', "_").as_str() {
+            "slowest" => Ok(LatePolicy::Slowest),
+            "forfeit" => Ok(LatePolicy::Forfeit),
+            _ => Err(format!("Unknown late policy '{}', expected slowest or forfeit.", policy))
+        };
+    }
 }
 
-fn deal_cards_to_players(players: i32, tx_deck: Sender<Vec<Card>>) -> (i32, i32){
-    let deck_size = FRENCH_DECK_SIZE / players;
-    let unused_cards = FRENCH_DECK_SIZE % players;
-    let full_deck = random_full_deck();
+/* Cartas que no se repartieron, separadas segun la politica de la partida. */
+#[derive(Debug, Default, PartialEq)]
+struct Leftovers {
+    kitty: Option<Card>,
+    draw_pile: Vec<Card>,
+    tiebreak_deck: Vec<Card>
+}
 
+fn set_aside_leftovers(cards: Vec<Card>, policy: LeftoverPolicy) -> Leftovers {
+    return match policy {
+        LeftoverPolicy::Discard => Leftovers::default(),
+        LeftoverPolicy::Kitty => Leftovers{kitty: cards.first().copied(), ..Leftovers::default()},
+        LeftoverPolicy::DrawPile => Leftovers{draw_pile: cards, ..Leftovers::default()},
+        LeftoverPolicy::TiebreakDeck => Leftovers{tiebreak_deck: cards, ..Leftovers::default()}
+    };
+}
+
+/* Suma los puntos del palo bonificado a cada jugador que tiro una carta de ese palo. */
+fn apply_kitty_bonus(hand_outcome: &mut HandOutcome, cards: &[SignedCard], bonus_suit: CardSuit, bonus: Points) {
+    for signed_card in cards.iter().filter(|signed_card| signed_card.card.suit == bonus_suit) {
+        *hand_outcome.earned_points.entry(signed_card.player_signature).or_insert_with(|| points(0)) += bonus;
+    }
+}
+
+/* Todos los jugadores reciben la misma cantidad de cartas; si la division no es exacta (por ejemplo
+con una cantidad impar de jugadores) sobran cartas, que se devuelven para usarlas segun la politica
+de sobrantes. Si el zapato no alcanza para todos se le agregan mazos para que nadie reciba una mano
+vacia. Devuelve la mano de cada jugador, en orden de asiento. */
+pub fn deal_cards_to_players(players: i32, deck: &DeckBuilder, rng: &mut StdRng) -> (Vec<Vec<Card>>, Vec<Card>){
+    let shoe = deck.for_players(players);
+    let deck_size = shoe.size() / players;
+    let full_deck = shoe.build(rng);
+
+    let mut hands = Vec::new();
     for p in 0..players {
         let lower_bound = p*deck_size;
         let upper_bound = (p+1)*deck_size;
-        let cards = &full_deck[lower_bound as usize..upper_bound as usize];
-        tx_deck.send(cards.to_vec()).unwrap();
+        hands.push(full_deck[lower_bound as usize..upper_bound as usize].to_vec());
     }
 
-    return (deck_size, unused_cards);
+    return (hands, full_deck[(players*deck_size) as usize..].to_vec());
 }
 
-fn player_fixed_values_map(players: i32, value: i32) -> HashMap<i32, i32>{
+fn player_fixed_values_map<T: Copy>(players: i32, value: T) -> HashMap<i32, T>{
     let mut points_by_user = HashMap::new();
 
     for p in 0..players {
@@ -48,7 +149,7 @@ fn player_fixed_values_map(players: i32, value: i32) -> HashMap<i32, i32>{
     return points_by_user
 }
 
-fn merge_points_hashmaps(map1: HashMap<i32, i32>, map2: HashMap<i32, i32>) -> HashMap<i32, i32>{
+pub fn merge_points_hashmaps<T: Copy + Add<Output = T>>(map1: HashMap<i32, T>, map2: HashMap<i32, T>) -> HashMap<i32, T>{
     let mut merged = HashMap::new();
     for key in map1.keys() {
         if map2.contains_key(key){
@@ -60,221 +161,528 @@ fn merge_points_hashmaps(map1: HashMap<i32, i32>, map2: HashMap<i32, i32>) -> Ha
     return merged;
 }
 
-fn calculate_normal_hand_points(mut signed_cards: Vec<SignedCard>) -> HandOutcome{
-    let mut hand_outcome = HandOutcome{earned_points: player_fixed_values_map(signed_cards.len() as i32, 0),
-                                        max_card_points: 0, players_with_max_card: HashSet::new(),
-                                        slowest_player: None, fastest_player: None};
+fn register_current_points(logfile: LogFile, points_by_user: &HashMap<i32, Points>) -> std::io::Result<()>{
+    for (user, points ) in points_by_user {
+        debug(logfile.clone(), format!("El jugador con id {} posee {} puntos.", user, points))?;
+    }
+    return Ok(());
+}
 
-    signed_cards.sort_by(|a, b| a.card.number.cmp(&b.card.number));
 
-    let max_card: Card = signed_cards.last().unwrap().card;
-    let mut i = signed_cards.len() - 1;
-    while signed_cards[i].card.number == max_card.number {
-        hand_outcome.players_with_max_card.insert(signed_cards[i].player_signature);
-        if i == 0{
-            break;
+fn keep_playing(available_cards_by_user: &HashMap<i32,i32>) -> bool{
+    for available_cards in available_cards_by_user.values(){
+        if *available_cards == 0 {
+            return false;
         }
-        i-=1;
     }
+    return true;
+}
 
-    hand_outcome.max_card_points = POINTS_MAX_CARD/(hand_outcome.players_with_max_card.len() as i32);
+fn is_suspended(suspended_rounds_by_user: &HashMap<i32, i32>, player: i32) -> bool {
+    return *suspended_rounds_by_user.get(&player).unwrap_or(&0) > 0;
+}
 
-    for p in &hand_outcome.players_with_max_card{
-        hand_outcome.earned_points.insert(*p, hand_outcome.max_card_points);
+fn update_suspensions(suspended_rounds_by_user: &mut HashMap<i32, i32>, slowest_player: Option<i32>,
+                      suspension_rounds: i32) {
+    for remaining_rounds in suspended_rounds_by_user.values_mut() {
+        if *remaining_rounds > 0 {
+            *remaining_rounds -= 1;
+        }
+    }
+    if let Some(slowest_player) = slowest_player {
+        suspended_rounds_by_user.insert(slowest_player, suspension_rounds);
     }
-
-    return hand_outcome;
 }
 
-fn register_current_points(logfile: LogFile, points_by_user: &HashMap<i32, i32>) -> std::io::Result<()>{
-    for (user, points ) in points_by_user {
-        debug(logfile.clone(), format!("El jugador con id {} posee {} puntos.", user, points))?;
+fn determine_hand_outcome(cards: Vec<SignedCard>, normal: bool, max_card_pot: Points, late_players: &[i32],
+                          scorers: &RoundScorers) -> HandOutcome {
+    /* Puede pasar si nadie llego a tiempo en una ronda rustica. */
+    return if cards.is_empty() && late_players.is_empty() {
+        HandOutcome{carried_points: max_card_pot, ..empty_hand_outcome(&cards)}
+    } else if normal {
+        scorers.normal.score(cards, max_card_pot, late_players)
+    } else {
+        scorers.rustic.score(cards, max_card_pot, late_players)
     }
-    return Ok(());
 }
 
+/* Estado de una partida en curso: reparte, decide el tipo de cada ronda, puntua las cartas que se
+tiraron y lleva las suspensiones. No sabe como llegan las cartas, asi que lo comparten todos los
+motores de juego. */
+pub struct GameState {
+    logfile: LogFile,
+    players: i32,
+    seed: u64,
+    ruleset: Ruleset,
+    scorers: RoundScorers,
+    crash_policy: CrashPolicy,
+    /* Un unico generador alimenta el mezclado del mazo y la eleccion del tipo de ronda, de modo que
+    la misma semilla reproduce exactamente la misma partida. */
+    rng: StdRng,
+    leftovers: Leftovers,
+    pending_draws: HashMap<i32, Card>,
+    points_by_user: HashMap<i32, Points>,
+    max_card_pot: Points,
+    available_cards_by_user: HashMap<i32, i32>,
+    suspended_rounds_by_user: HashMap<i32, i32>,
+    round: i32,
+    round_type: RoundType,
+    suspended_players: Vec<i32>,
+    late_players: Vec<i32>,
+    /* Jugadores que todavia deben la carta que no llegaron a tirar a tiempo. Cuando llega se
+    descarta. */
+    late_cards: Vec<i32>,
+    /* Jugadores que se cayeron. No vuelven a jugar pero conservan sus puntos. */
+    eliminated_players: Vec<i32>,
+    /* Jugadores que no tiraron a tiempo y todavia no terminaron su ronda. Las rondas siguen sin ellos
+    hasta que vuelven. */
+    absent_players: Vec<i32>,
+    /* Motivo por el que se interrumpe la partida al terminar la ronda en curso. */
+    abort_reason: Option<String>,
+    rounds: Vec<RoundResult>
+}
+
+impl GameState {
+    /* Devuelve el estado inicial y la mano de cada jugador, en orden de asiento. */
+    pub fn new(logfile: LogFile, settings: GameSettings) -> std::io::Result<(GameState, Vec<Vec<Card>>)> {
+        let players = settings.players;
+        let ruleset = settings.ruleset;
+        info(logfile.clone(), format!("Semilla de la partida: {}", settings.seed))?;
+        ruleset.register(logfile.clone())?;
 
-fn calculate_rustic_hand_points(signed_cards: Vec<SignedCard>) -> HandOutcome {
-    let mut hand_outcome = HandOutcome{earned_points: player_fixed_values_map(signed_cards.len() as i32, 0),
-                                        max_card_points: 0, players_with_max_card: HashSet::new(),
-                                        slowest_player: None, fastest_player: None};
+        let mut rng = StdRng::seed_from_u64(settings.seed);
 
-    let first_player = signed_cards.first().unwrap();
-    hand_outcome.earned_points.insert(first_player.player_signature, POINTS_FASTER_PLAYER);
-    hand_outcome.fastest_player = Some(first_player.player_signature);
+        let shoe_decks = ruleset.deck.for_players(players).decks;
+        if shoe_decks > 1 {
+            info(logfile.clone(), format!("Se juega con un zapato de {} mazos", shoe_decks))?;
+        }
+        let (hands, unused_cards) = deal_cards_to_players(players, &ruleset.deck, &mut rng);
+        let deck_size = hands[0].len() as i32;
+        info(logfile.clone(), format!("Hay {} del mazo sin usar", unused_cards.len()))?;
+        let leftovers = set_aside_leftovers(unused_cards, ruleset.leftover);
+        if let Some(kitty) = leftovers.kitty {
+            info(logfile.clone(), format!("La carta boca arriba es el {} de {}: el palo {} suma {} puntos por carta",
+                                          kitty.number, kitty.suit.name(), kitty.suit.name(), ruleset.points_kitty_suit))?;
+        }
 
-    let last_player = *signed_cards.last().unwrap();
-    hand_outcome.earned_points.insert(last_player.player_signature, POINTS_SLOWER_PLAYER);
-    hand_outcome.slowest_player = Some(last_player.player_signature);
+        let state = GameState{logfile, players, seed: settings.seed, ruleset, scorers: settings.scorers,
+                              crash_policy: settings.crash_policy, rng, leftovers,
+                              pending_draws: HashMap::new(), points_by_user: player_fixed_values_map(players, points(0)),
+                              max_card_pot: points(0),
+                              available_cards_by_user: player_fixed_values_map(players, deck_size),
+                              suspended_rounds_by_user: player_fixed_values_map(players, 0),
+                              round: 1, round_type: RoundType::default(), suspended_players: vec![],
+                              late_players: vec![], late_cards: vec![], eliminated_players: vec![],
+                              absent_players: vec![],
+                              abort_reason: None, rounds: Vec::new()};
+        return Ok((state, hands));
+    }
 
-    let normal_hand_outcome = calculate_normal_hand_points(signed_cards);
+    pub fn players(&self) -> i32 {
+        return self.players;
+    }
 
-    hand_outcome.earned_points = merge_points_hashmaps(hand_outcome.earned_points, normal_hand_outcome.earned_points);
-    hand_outcome.players_with_max_card = normal_hand_outcome.players_with_max_card;
-    hand_outcome.max_card_points = normal_hand_outcome.max_card_points;
+    /* Se juega mientras a todos los que siguen en la partida les queden cartas. */
+    pub fn keep_playing(&self) -> bool {
+        let active_players = self.available_cards_by_user.iter()
+            .filter(|(player, _)| !self.is_eliminated(**player))
+            .map(|(player, cards)| (*player, *cards))
+            .collect();
+        return self.abort_reason.is_none() && keep_playing(&active_players);
+    }
 
-    return hand_outcome;
-}
+    pub fn is_eliminated(&self, player: i32) -> bool {
+        return self.eliminated_players.contains(&player);
+    }
 
-fn keep_playing(available_cards_by_user: &HashMap<i32,i32>) -> bool{
-    for available_cards in available_cards_by_user.values(){
-        if *available_cards == 0 {
-            return false;
+    pub fn is_absent(&self, player: i32) -> bool {
+        return self.absent_players.contains(&player);
+    }
+
+    /* true si todos los que siguen en la partida estan ausentes y no hay a quien avisarle la ronda. */
+    pub fn everyone_absent(&self) -> bool {
+        return !self.absent_players.is_empty() &&
+            (0..self.players).all(|p| self.is_eliminated(p) || self.is_absent(p));
+    }
+
+    pub fn player_absent(&mut self, player: i32) -> std::io::Result<()> {
+        self.absent_players.push(player);
+        return info(self.logfile.clone(), format!("La partida sigue sin esperar al jugador {}", player));
+    }
+
+    pub fn player_returned(&mut self, player: i32) -> std::io::Result<()> {
+        self.absent_players.retain(|absent_player| *absent_player != player);
+        return info(self.logfile.clone(), format!("El jugador {} vuelve a la partida", player));
+    }
+
+    /* Saca de la partida a un jugador que se cayo. Con CrashPolicy::Abort, o si no quedan jugadores
+    suficientes, la partida se interrumpe al terminar la ronda. */
+    pub fn player_crashed(&mut self, player: i32, reason: &str) -> std::io::Result<()> {
+        if self.is_eliminated(player) {
+            return Ok(());
         }
+        error(self.logfile.clone(), format!("El jugador {} se cayó: {}", player, reason))?;
+        self.eliminated_players.push(player);
+        self.late_cards.retain(|late_player| *late_player != player);
+        self.absent_players.retain(|absent_player| *absent_player != player);
+        let remaining_players = self.players - self.eliminated_players.len() as i32;
+        if self.crash_policy == CrashPolicy::Abort {
+            self.abort_reason = Some(format!("Player {} crashed ({}), the game was aborted.", player, reason));
+        } else if remaining_players < MIN_PLAYERS {
+            self.abort_reason = Some(format!("Player {} crashed ({}) and only {} players are left, the game was aborted.",
+                                             player, reason, remaining_players));
+        }
+        match &self.abort_reason {
+            Some(_) => error(self.logfile.clone(), "La partida se interrumpe al terminar la ronda".to_string())?,
+            None => info(self.logfile.clone(), format!("El jugador {} queda eliminado, la partida sigue con {} jugadores",
+                                                       player, remaining_players))?
+        }
+        return Ok(());
     }
-    return true;
-}
 
-fn determine_hand_outcome(cards: Vec<SignedCard>, normal: bool) -> HandOutcome {
-    return if normal {
-        calculate_normal_hand_points(cards)
-    } else {
-        return calculate_rustic_hand_points(cards);
+    pub fn abort_reason(&self) -> Option<String> {
+        return self.abort_reason.clone();
     }
-}
 
-pub fn coordinator(logfile: LogFile, players: i32, card_receiver: Receiver<SignedCard>,
-                   barrier: Arc<Barrier>, tx_deck :Sender<Vec<Card>>,
-                   cond_vars_players: HashMap<i32, Arc<(Mutex<RoundPlayerFlags>, Condvar)>>) -> std::io::Result<()>{
-    let (deck_size, unused_cards) = deal_cards_to_players(players, tx_deck);
-    info(logfile.clone(), format!("Hay {} del mazo sin usar", unused_cards))?;
+    /* Elige el tipo de la proxima ronda. */
+    pub fn start_round(&mut self) -> std::io::Result<RoundType> {
+        info(self.logfile.clone(), format!("Iniciando ronda {}", self.round))?;
+        let normal: bool = self.rng.gen_bool(self.ruleset.normal_round_probability);
 
-    let mut rng = rand::thread_rng();
+        if normal {
+            debug(self.logfile.clone(), "La ronda es de tipo normal".to_string())?;
+        } else {
+            debug(self.logfile.clone(), "La ronda es de tipo rústica".to_string())?;
+        }
+        self.round_type = if normal { RoundType::Normal } else { RoundType::Rustic };
+        self.suspended_players = (0..self.players).filter(|p| self.is_suspended(*p)).collect();
+        return Ok(self.round_type);
+    }
 
-    let mut points_by_user = player_fixed_values_map(players, 0);
-    let mut available_cards_by_user = player_fixed_values_map(players, deck_size);
+    pub fn is_suspended(&self, player: i32) -> bool {
+        return is_suspended(&self.suspended_rounds_by_user, player);
+    }
 
-    let mut round = 1;
+    /* Aviso de turno para un jugador. En las rondas normales las cartas se reciben en orden, asi que
+    el jugador ve las de los que tiraron antes que el; en las rusticas todavia no tiro nadie. */
+    pub fn turn(&mut self, player: i32, cards_played: &[SignedCard]) -> Turn {
+        let table = TableView{player_id: player, cards_thrown: 0, round: self.round, round_type: self.round_type,
+                              cards_played: cards_played.to_vec(), points_by_user: self.points_by_user.clone(),
+                              suspended_players: self.suspended_players.clone()};
+        return Turn{can_throw_card: !self.is_suspended(player), drawn_card: self.pending_draws.remove(&player), table,
+                    last_round: self.rounds.last().cloned()};
+    }
 
-    let mut suspended_player: Option<i32> = None;
+    /* Cuanto se espera a las cartas de una ronda rustica, None si se espera sin limite. */
+    pub fn rustic_deadline(&self) -> Option<Duration> {
+        return self.ruleset.rustic_deadline_ms.map(Duration::from_millis);
+    }
 
-    while keep_playing(&available_cards_by_user){
-        info(logfile.clone(), format!("Iniciando ronda {}", round))?;
+    /* Anota a los jugadores que no tiraron antes del tiempo limite de la ronda en curso. Sus cartas
+    se descartan cuando llegan. */
+    pub fn register_late_players(&mut self, late_players: Vec<i32>) -> std::io::Result<()> {
+        for player in &late_players {
+            info(self.logfile.clone(), format!("El jugador {} no tiró su carta antes del tiempo límite de la ronda {}: {}",
+                                               player, self.round, self.ruleset.late_policy.description()))?;
+        }
+        self.late_cards.extend(late_players.iter().copied());
+        self.late_players = late_players;
+        return Ok(());
+    }
 
-        let mut cards = Vec::new();
+    /* true si la carta es de una ronda anterior en la que el jugador no llego a tiempo. */
+    pub fn is_late_card(&mut self, signed_card: &SignedCard) -> std::io::Result<bool> {
+        let position = match self.late_cards.iter().position(|player| *player == signed_card.player_signature) {
+            Some(position) => position,
+            None => return Ok(false)
+        };
+        self.late_cards.remove(position);
+        debug(self.logfile.clone(), format!("Se descarta la carta del jugador {} que llegó fuera de tiempo",
+                                            signed_card.player_signature))?;
+        return Ok(true);
+    }
 
-        let normal: bool = rng.gen();
+    pub fn register_card(&self, signed_card: &SignedCard) -> std::io::Result<()> {
+        return debug(self.logfile.clone(), format!("Se recibio del jugador {} carta de número {}",
+                                                   signed_card.player_signature, signed_card.card.number));
+    }
 
-        if normal {
-            debug(logfile.clone(), "La ronda es de tipo normal".to_string())?;
-        } else {
-            debug(logfile.clone(), "La ronda es de tipo rústica".to_string())?;
+    /* Puntua la ronda con las cartas en el orden en que llegaron. */
+    pub fn finish_round(&mut self, cards: Vec<SignedCard>) -> std::io::Result<()> {
+        let logfile = self.logfile.clone();
+        let ruleset = self.ruleset;
+        let normal = self.round_type == RoundType::Normal;
+        let late_players = std::mem::take(&mut self.late_players);
+        let penalized_players: &[i32] = match ruleset.late_policy {
+            LatePolicy::Slowest => &late_players,
+            LatePolicy::Forfeit => &[]
+        };
+        let mut hand_outcome = determine_hand_outcome(cards.clone(), normal, self.max_card_pot, penalized_players,
+                                                      &self.scorers);
+        if let Some(kitty) = self.leftovers.kitty {
+            apply_kitty_bonus(&mut hand_outcome, &cards, kitty.suit, points(ruleset.points_kitty_suit));
         }
 
-        if normal {
-            barrier.wait();
-            debug(logfile.clone(), "El coordinador se prepara para recibir las cartas en \
-            el orden de las agujas del reloj".to_string())?;
+        self.points_by_user = merge_points_hashmaps(self.points_by_user.clone(), hand_outcome.earned_points.clone());
+        if let Some(fastest_player) = hand_outcome.fastest_player {
+            debug(logfile.clone(),format!("Ronda rústica: el jugador con id {} ha sido el mas rapido, \
+            sumando {} puntos", fastest_player, ruleset.points_faster_player))?;
         }
 
-        for p in 0..players {
-            /* Scope artificial creado para poder liberar el lock que se adquiere al obtener la variable
-            can_play. Si no generamos este scope, el player nunca puede adquirir el lock y hay un deadlock
-            cuando intenta adquirir el valor de la condition variable. */
-            {
-                let cond_var = cond_vars_players.get(&p).unwrap();
-                let (lock, cvar) = &**cond_var;
-                let mut round_player_flags = lock.lock().unwrap();
-
-                if suspended_player.is_some() && suspended_player.unwrap() == p {
-                    *round_player_flags = RoundPlayerFlags{is_my_turn: true, can_throw_card: false, game_ended: false}
-                } else {
-                    *round_player_flags = RoundPlayerFlags{is_my_turn: true, can_throw_card: true, game_ended: false}
-                }
-                cvar.notify_one();
-            }
+        if let Some(slowest_player) = hand_outcome.slowest_player {
+            debug(logfile.clone(),format!("Ronda rústica: el jugador con id {} ha sido el mas lento, \
+            restando {} puntos y perdiendo sus proximos {} turnos", slowest_player, ruleset.points_slower_player,
+            ruleset.suspension_rounds))?;
+        }
+
+        debug(logfile.clone(), format!("Los jugadores con ids {:?} ganan {} puntos por tirar la máxima carta de la ronda.",
+                hand_outcome.players_with_max_card, hand_outcome.max_card_points))?;
 
-            if suspended_player.is_some() && suspended_player.unwrap() == p {
+        if hand_outcome.carried_points != points(0) {
+            debug(logfile.clone(), format!("Quedan {} puntos en el pozo para la próxima ronda.",
+                                           hand_outcome.carried_points))?;
+        }
+        self.max_card_pot = hand_outcome.carried_points;
+
+        register_current_points(logfile.clone(), &self.points_by_user)?;
+
+        /* Los ausentes no tiran carta, salvo en la ronda en la que llegaron tarde. */
+        for p in 0..self.players {
+            if self.is_suspended(p) || self.is_eliminated(p) || (self.is_absent(p) && !late_players.contains(&p)) {
                 continue;
             }
+            let current_cards = *self.available_cards_by_user.get(&p).unwrap();
+            self.available_cards_by_user.insert(p, current_cards - 1);
+        }
 
-            if normal {
-                let signed_card = card_receiver.recv().unwrap();
-                debug(logfile.clone(), format!("Se recibio del jugador {} carta de número {}",
-                                                signed_card.player_signature,
-                                                signed_card.card.number))?;
-                cards.push(signed_card);
+        if let Some(fastest_player) = hand_outcome.fastest_player {
+            if let Some(card) = self.leftovers.draw_pile.pop() {
+                debug(logfile.clone(), format!("El jugador con id {} roba una carta del pozo, quedan {}",
+                                               fastest_player, self.leftovers.draw_pile.len()))?;
+                self.pending_draws.insert(fastest_player, card);
+                *self.available_cards_by_user.get_mut(&fastest_player).unwrap() += 1;
             }
         }
 
-        if !normal{
-            barrier.wait();
-            for p in 0..players{
-                if suspended_player.is_some() && suspended_player.unwrap() == p {
-                    continue;
-                }
-                let signed_card = card_receiver.recv().unwrap();
-                debug(logfile.clone(), format!("Se recibio del jugador {} carta de número {}",
-                                                signed_card.player_signature,
-                                                signed_card.card.number))?;
-                cards.push(signed_card);
-            }
+        for (player, cards) in self.available_cards_by_user.iter() {
+            debug(logfile.clone(), format!("El jugador con id {} aún tiene {} cartas por jugar", player, cards))?;
         }
 
-        let hand_outcome = determine_hand_outcome(cards, normal);
+        update_suspensions(&mut self.suspended_rounds_by_user, hand_outcome.slowest_player, ruleset.suspension_rounds);
+        self.rounds.push(RoundResult{round: self.round, round_type: self.round_type, cards, hand_outcome,
+                                     suspended_players: self.suspended_players.clone(), late_players});
+        self.round += 1;
+        return Ok(());
+    }
 
-        points_by_user = merge_points_hashmaps(points_by_user, hand_outcome.earned_points);
-        if hand_outcome.fastest_player.is_some(){
-            debug(logfile.clone(),format!("Ronda rústica: el jugador con id {} ha sido el mas rapido, \
-            sumando {} puntos", hand_outcome.fastest_player.unwrap(), POINTS_FASTER_PLAYER))?;
+    /* Desempata si hace falta y arma el resultado de la partida. */
+    pub fn finish_game(mut self) -> std::io::Result<GameResult> {
+        let logfile = self.logfile.clone();
+        /* Los eliminados conservan sus puntos pero no pueden ganar. */
+        let candidates: HashMap<i32, Points> = self.points_by_user.iter()
+            .filter(|(player, _)| !self.is_eliminated(**player))
+            .map(|(player, points)| (*player, *points))
+            .collect();
+        let leaders = players_with_max_points(&candidates);
+        if leaders.len() > 1 {
+            info(logfile.clone(), format!("Los jugadores {:?} empatan con {} puntos", leaders,
+                                          self.points_by_user.get(&leaders[0]).unwrap()))?;
+        }
+        let tiebreak = break_tie(leaders, &self.ruleset, &self.rounds, &self.leftovers.tiebreak_deck, &mut self.rng);
+        if let Some(decided_by) = tiebreak.decided_by {
+            info(logfile.clone(), format!("El empate se resuelve por {}", decided_by.description()))?;
         }
 
-        if hand_outcome.slowest_player.is_some(){
-            debug(logfile.clone(),format!("Ronda rústica: el jugador con id {} ha sido el mas lento, \
-            restando {} puntos y perdiendo su proximo turno", hand_outcome.slowest_player.unwrap(), POINTS_SLOWER_PLAYER))?;
+        let game_result = GameResult::new(self.seed, self.points_by_user, self.rounds, tiebreak.winners,
+                                          tiebreak.decided_by, self.eliminated_players);
+
+        if game_result.winners.len() == 1 {
+            info(logfile.clone(), format!("El ganador es {} con {} puntos", game_result.winners[0],
+                                          game_result.winner_points()))?;
+        } else {
+            info(logfile.clone(), format!("Los ganadores son {:?} con {} puntos", game_result.winners,
+                                          game_result.winner_points()))?;
         }
+        return Ok(game_result);
+    }
+}
 
-        debug(logfile.clone(), format!("Los jugadores con ids {:?} ganan {} puntos por tirar la máxima carta de la ronda.",
-                hand_outcome.players_with_max_card, hand_outcome.max_card_points))?;
+/* Espera la proxima carta de la ronda hasta el tiempo limite, salteando las que llegan tarde de
+rondas anteriores. None si se termino el tiempo o si se cayo un jugador. */
+async fn receive_card<T: CoordinatorTransport>(transport: &mut T, state: &mut GameState,
+                                               deadline: Option<Instant>) -> std::io::Result<Option<SignedCard>> {
+    loop {
+        let event = match deadline {
+            Some(deadline) => match transport.receive_card_timeout(deadline.saturating_duration_since(Instant::now())).await {
+                Some(event) => event,
+                None => return Ok(None)
+            },
+            None => transport.receive_card().await
+        };
+        match event {
+            PlayerEvent::Card(signed_card) => if !state.is_late_card(&signed_card)? {
+                return Ok(Some(signed_card));
+            },
+            PlayerEvent::Crashed{player_id, reason} => {
+                state.player_crashed(player_id, &reason)?;
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/* Procesa lo que quedo en el canal sin esperar: descarta las cartas que llegaron tarde y elimina a los
+que se cayeron. */
+async fn drain_events<T: CoordinatorTransport>(transport: &mut T, state: &mut GameState) -> std::io::Result<()> {
+    while let Some(event) = transport.receive_card_timeout(Duration::ZERO).await {
+        match event {
+            PlayerEvent::Card(signed_card) => {
+                state.is_late_card(&signed_card)?;
+            },
+            PlayerEvent::Crashed{player_id, reason} => state.player_crashed(player_id, &reason)?
+        }
+    }
+    return Ok(());
+}
 
-        register_current_points(logfile.clone(), &points_by_user)?;
+/* Jugadores que tiran carta en la ronda en curso. */
+fn throwing_players(state: &GameState) -> Vec<i32> {
+    return (0..state.players())
+        .filter(|p| !state.is_suspended(*p) && !state.is_eliminated(*p) && !state.is_absent(*p))
+        .collect();
+}
+
+/* Vuelve a esperar en las rondas a los ausentes que ya terminaron la suya. Si estan todos ausentes
+espera a que vuelva o se caiga alguno. */
+async fn readmit_players<T: CoordinatorTransport>(transport: &mut T, state: &mut GameState) -> std::io::Result<()> {
+    let everyone_absent = state.everyone_absent();
+    for p in transport.readmit(everyone_absent).await {
+        state.player_returned(p)?;
+    }
+    if everyone_absent {
+        drain_events(transport, state).await?;
+    }
+    return Ok(());
+}
+
+/* Aviso de fin de partida para cada jugador. */
+pub fn game_over(game_result: &GameResult) -> GameOver {
+    return GameOver{points_by_user: game_result.points_by_user.clone(), last_round: game_result.rounds.last().cloned(),
+                    winners: game_result.winners.clone()};
+}
+
+/* Juega la partida del lado del coordinador. Lo comparten el motor con hilos y el de tokio, que solo
+cambian el transporte. */
+pub async fn coordinator<T: CoordinatorTransport>(logfile: LogFile, settings: GameSettings,
+                                                  mut transport: T) -> std::io::Result<GameResult>{
+    let (mut state, hands) = GameState::new(logfile.clone(), settings)?;
+    let players = state.players();
+    for (p, hand) in (0..players).zip(hands) {
+        transport.deal(p, hand);
+    }
+
+    while state.keep_playing() {
+        readmit_players(&mut transport, &mut state).await?;
+        if state.everyone_absent() {
+            continue;
+        }
+        let mut cards = Vec::new();
+        let normal = state.start_round()? == RoundType::Normal;
+
+        if normal {
+            transport.sync_round().await;
+            debug(logfile.clone(), "El coordinador se prepara para recibir las cartas en \
+            el orden de las agujas del reloj".to_string())?;
+        }
 
         for p in 0..players {
-            if suspended_player.is_some() && suspended_player.unwrap() == p {
+            if state.is_eliminated(p) || state.is_absent(p) {
                 continue;
             }
-            let current_cards = *available_cards_by_user.get(&p).unwrap();
-            available_cards_by_user.insert(p, current_cards - 1);
+            let turn = state.turn(p, &cards);
+            transport.notify_turn(p, turn);
+
+            if state.is_suspended(p) {
+                continue;
+            }
+
+            /* Si se cae un jugador se sigue esperando, salvo que el que se cayo sea al que le toca. */
+            while normal && !state.is_eliminated(p) {
+                if let Some(signed_card) = receive_card(&mut transport, &mut state, None).await? {
+                    state.register_card(&signed_card)?;
+                    cards.push(signed_card);
+                    break;
+                }
+            }
         }
 
-        for (player, cards) in available_cards_by_user.iter() {
-            debug(logfile.clone(), format!("El jugador con id {} aún tiene {} cartas por jugar", player, cards))?;
+        if !normal{
+            transport.sync_round().await;
+            let deadline = state.rustic_deadline().map(|deadline| Instant::now() + deadline);
+            let mut late_players = throwing_players(&state);
+            while !late_players.is_empty() {
+                match receive_card(&mut transport, &mut state, deadline).await? {
+                    Some(signed_card) => {
+                        late_players.retain(|p| *p != signed_card.player_signature);
+                        state.register_card(&signed_card)?;
+                        cards.push(signed_card);
+                    },
+                    None if deadline.is_some_and(|deadline| Instant::now() >= deadline) => break,
+                    None => late_players.retain(|p| !state.is_eliminated(*p))
+                }
+            }
+            /* Para que la proxima ronda no espere a los que todavia no tiraron. */
+            for p in &late_players {
+                if transport.excuse(*p) {
+                    state.player_absent(*p)?;
+                }
+            }
+            state.register_late_players(late_players)?;
         }
 
-        suspended_player = hand_outcome.slowest_player;
-        round += 1;
+        state.finish_round(cards)?;
 
-        barrier.wait();
+        transport.sync_round().await;
     }
 
     // Ultima iteracion para avisar el fin
 
-    barrier.wait();
-
-    for p in 0..players {
-        {
-            let cond_var = cond_vars_players.get(&p).unwrap();
-            let (lock, cvar) = &**cond_var;
-            let mut round_player_flags = lock.lock().unwrap();
-            *round_player_flags = RoundPlayerFlags{is_my_turn: true, can_throw_card: true, game_ended: true};
-            cvar.notify_one();
-        }
+    for p in transport.readmit(false).await {
+        state.player_returned(p)?;
     }
 
-    barrier.wait();
+    transport.sync_round().await;
 
-    let (winner, winner_points) = points_by_user.iter().max_by_key(|k| k.1).unwrap();
+    let abort_reason = state.abort_reason();
+    let game_result = state.finish_game()?;
 
-    info(logfile.clone(), format!("El ganador es {} con {} puntos", winner, winner_points))?;
+    for p in 0..players {
+        transport.end_game(p, game_over(&game_result));
+    }
 
-    return Ok(());
+    transport.sync_round().await;
+
+    if let Some(reason) = abort_reason {
+        return Err(std::io::Error::other(reason));
+    }
+    return Ok(game_result);
 }
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_cast)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
-    use crate::cards::CardSuit;
+    use crate::cards::{CardSuit, DeckKind};
+    use crate::round_scorer::RoundScorer;
+    use crate::ruleset::{POINTS_MAX_CARD, POINTS_SLOWER_PLAYER};
+    use crate::tiebreak::TiebreakPolicy;
+    use crate::game::Game;
+    use crate::reaction::ReactionTime;
+    use crate::game::Engine;
+    use crate::strategy::{PlayerStrategy, TableView};
+    use std::collections::HashSet;
+    use std::sync::{mpsc, Arc, Mutex};
 
     #[test]
     fn test_empty_points_map_len() {
@@ -285,6 +693,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_deal_cards_to_any_player_count() {
+        for (players, deck_size, unused_cards) in [(2, 26, 0), (5, 10, 2), (52, 1, 0), (53, 1, 51)].iter() {
+            let (hands, unused) = deal_cards_to_players(*players, &DeckBuilder::default(), &mut StdRng::seed_from_u64(1));
+            assert_eq!(unused.len(), *unused_cards);
+            assert_eq!(hands.len(), *players as usize);
+            assert!(hands.iter().all(|hand| hand.len() == *deck_size as usize));
+        }
+    }
+
+    #[test]
+    fn test_deal_cards_from_spanish_deck_with_jokers() {
+        let deck = DeckBuilder::new(DeckKind::Spanish40).jokers(2);
+        let (hands, unused) = deal_cards_to_players(6, &deck, &mut StdRng::seed_from_u64(1));
+        assert_eq!(unused, vec![]);
+        assert!(hands.iter().all(|hand| hand.len() == 7));
+        let jokers = hands.iter().flatten().filter(|card| card.suit == CardSuit::Joker).count();
+        assert_eq!(jokers, 2);
+    }
+
     #[test]
     fn test_merge_same_keys() {
         let mut map1 = HashMap::new();
@@ -317,6 +745,25 @@ mod tests {
         assert_eq!(*merged_map.get(&(5 as i32)).unwrap(), 7 as i32);
     }
 
+    #[test]
+    fn test_update_suspensions_counts_down() {
+        let mut suspended_rounds_by_user = player_fixed_values_map(4, 0);
+        update_suspensions(&mut suspended_rounds_by_user, Some(2), 2);
+        assert!(is_suspended(&suspended_rounds_by_user, 2));
+        update_suspensions(&mut suspended_rounds_by_user, None, 2);
+        assert!(is_suspended(&suspended_rounds_by_user, 2));
+        update_suspensions(&mut suspended_rounds_by_user, Some(1), 2);
+        assert!(!is_suspended(&suspended_rounds_by_user, 2));
+        assert!(is_suspended(&suspended_rounds_by_user, 1));
+    }
+
+    #[test]
+    fn test_zero_suspension_rounds_never_suspends() {
+        let mut suspended_rounds_by_user = player_fixed_values_map(4, 0);
+        update_suspensions(&mut suspended_rounds_by_user, Some(3), 0);
+        assert!(!is_suspended(&suspended_rounds_by_user, 3));
+    }
+
     #[test]
     fn test_keep_playing() {
         let mut map = HashMap::new();
@@ -328,88 +775,203 @@ mod tests {
         assert!(!keep_playing(&map));
     }
 
+    struct LowestCardScorer;
+
+    impl RoundScorer for LowestCardScorer {
+        fn score(&self, signed_cards: Vec<SignedCard>, _max_card_pot: Points, _late_players: &[i32]) -> HandOutcome {
+            let lowest = signed_cards.iter().min_by_key(|signed_card| signed_card.card.number).unwrap();
+            let mut earned_points = HashMap::new();
+            earned_points.insert(lowest.player_signature, points(1));
+            let mut players_with_max_card = HashSet::new();
+            players_with_max_card.insert(lowest.player_signature);
+            return HandOutcome{earned_points, max_card_points: points(1), carried_points: points(0),
+                               players_with_max_card, slowest_player: None, fastest_player: None};
+        }
+    }
+
+    #[test]
+    fn test_determine_hand_outcome_uses_configured_scorer() {
+        let scorers = RoundScorers{normal: Box::new(LowestCardScorer), ..RoundScorers::default()};
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 4, suit:CardSuit::Spades}, player_signature: 0},
+            SignedCard{card: Card{number: 9, suit:CardSuit::Hearts}, player_signature: 1},
+        ];
+        let normal_outcome = determine_hand_outcome(signed_cards.clone(), true, points(0), &[], &scorers);
+        assert!(normal_outcome.players_with_max_card.contains(&0));
+
+        let rustic_outcome = determine_hand_outcome(signed_cards, false, points(0), &[], &scorers);
+        assert!(rustic_outcome.players_with_max_card.contains(&1));
+        assert_eq!(rustic_outcome.fastest_player, Some(0));
+    }
+
+    fn undealt_cards(players: i32, seed: u64) -> Vec<Card> {
+        return deal_cards_to_players(players, &DeckBuilder::default(), &mut StdRng::seed_from_u64(seed)).1;
+    }
+
+    #[test]
+    fn test_discarded_leftovers_are_not_used() {
+        assert_eq!(set_aside_leftovers(undealt_cards(5, 1), LeftoverPolicy::Discard), Leftovers::default());
+    }
+
+    #[test]
+    fn test_kitty_sets_bonus_suit() {
+        let cards = undealt_cards(5, 1);
+        let leftovers = set_aside_leftovers(cards.clone(), LeftoverPolicy::Kitty);
+        assert_eq!(leftovers.kitty, Some(cards[0]));
+        assert!(leftovers.draw_pile.is_empty() && leftovers.tiebreak_deck.is_empty());
+        assert_eq!(set_aside_leftovers(vec![], LeftoverPolicy::Kitty).kitty, None);
+
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 4, suit:CardSuit::Hearts}, player_signature: 0},
+            SignedCard{card: Card{number: 9, suit:CardSuit::Spades}, player_signature: 1},
+            SignedCard{card: Card{number: 2, suit:CardSuit::Hearts}, player_signature: 2},
+        ];
+        let mut hand_outcome = determine_hand_outcome(signed_cards.clone(), true, points(0), &[], &RoundScorers::default());
+        apply_kitty_bonus(&mut hand_outcome, &signed_cards, CardSuit::Hearts, points(3));
+        assert_eq!(*hand_outcome.earned_points.get(&0).unwrap(), points(3));
+        assert_eq!(*hand_outcome.earned_points.get(&1).unwrap(), points(10));
+        assert_eq!(*hand_outcome.earned_points.get(&2).unwrap(), points(3));
+    }
+
     #[test]
-    fn test_calculate_normal_hand_points_one_player() {
-        let mut signed_cards = vec![];
-        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
-                                     player_signature: 0});
-        let hand_outcome = calculate_normal_hand_points(signed_cards);
-        assert_eq!(hand_outcome.earned_points.len(), 1);
-        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), POINTS_MAX_CARD as i32);
-        assert_eq!(hand_outcome.max_card_points, 10);
-        assert_eq!(hand_outcome.players_with_max_card.len(), 1);
-        assert!(hand_outcome.players_with_max_card.contains(&0));
-        assert!(!hand_outcome.slowest_player.is_some());
-        assert!(!hand_outcome.fastest_player.is_some());
+    fn test_kitty_bonus_is_scored_in_game() {
+        let ruleset = Ruleset{leftover: LeftoverPolicy::Kitty, points_max_card: 0, normal_round_probability: 1.0,
+                              ..Ruleset::default()};
+        let kitty = undealt_cards(5, 3)[0];
+        let result = Game::builder(5).seed(3).ruleset(ruleset).build().unwrap().run().unwrap();
+        for round in &result.rounds {
+            for signed_card in &round.cards {
+                let expected = if signed_card.card.suit == kitty.suit { points(1) } else { points(0) };
+                assert_eq!(*round.hand_outcome.earned_points.get(&signed_card.player_signature).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fastest_player_plays_card_from_draw_pile() {
+        let cards = undealt_cards(5, 5);
+        let ruleset = Ruleset{leftover: LeftoverPolicy::DrawPile, normal_round_probability: 0.0, ..Ruleset::default()};
+        let result = Game::builder(5).seed(5).ruleset(ruleset).build().unwrap().run().unwrap();
+        for (round, drawn_card) in result.rounds.iter().zip(cards.iter().rev()) {
+            let fastest_player = round.hand_outcome.fastest_player.unwrap();
+            let next_round = &result.rounds[round.round as usize];
+            let played = next_round.cards.iter().find(|signed_card| signed_card.player_signature == fastest_player);
+            assert_eq!(played.unwrap().card, *drawn_card);
+        }
+    }
+
+    #[test]
+    fn test_tiebreak_deck_decides_sudden_death() {
+        let leftovers = set_aside_leftovers(vec![Card{number: 3, suit: CardSuit::Clubs},
+                                                 Card{number: 9, suit: CardSuit::Hearts}],
+                                            LeftoverPolicy::TiebreakDeck);
+        assert!(leftovers.kitty.is_none() && leftovers.draw_pile.is_empty());
+        let ruleset = Ruleset{leftover: LeftoverPolicy::TiebreakDeck, tiebreak: TiebreakPolicy::SuddenDeath,
+                              ..Ruleset::default()};
+        let tiebreak = break_tie(vec![4, 7], &ruleset, &[], &leftovers.tiebreak_deck, &mut StdRng::seed_from_u64(1));
+        assert_eq!(tiebreak.winners, vec![7]);
+        assert_eq!(tiebreak.decided_by, Some(TiebreakPolicy::SuddenDeath));
+    }
+
+    #[test]
+    fn test_eliminated_leader_does_not_win() {
+        let settings = GameSettings{players: 3, seed: 1, ruleset: Ruleset::default(), scorers: RoundScorers::default(),
+                                    crash_policy: CrashPolicy::Eliminate};
+        let (mut state, _) = GameState::new(Arc::new(Mutex::new(None)), settings).unwrap();
+        state.points_by_user.insert(0, points(30));
+        state.points_by_user.insert(1, points(10));
+        state.points_by_user.insert(2, points(5));
+        state.player_crashed(0, "boom").unwrap();
+        let result = state.finish_game().unwrap();
+        assert_eq!(result.winners, vec![1]);
+        assert_eq!(result.ranking, vec![1, 2, 0]);
+        assert_eq!(result.eliminated_players, vec![0]);
+        assert_eq!(result.points_by_user[&0], points(30));
+    }
+
+    #[test]
+    fn test_round_without_cards_keeps_the_pot() {
+        let outcome = determine_hand_outcome(vec![], false, points(3), &[], &RoundScorers::default());
+        assert!(outcome.earned_points.is_empty() && outcome.slowest_player.is_none());
+        assert_eq!(outcome.carried_points, points(3));
+        let outcome = determine_hand_outcome(vec![], false, points(3), &[1], &RoundScorers::default());
+        assert_eq!(outcome.earned_points[&1], points(POINTS_SLOWER_PLAYER));
+        assert_eq!(outcome.slowest_player, Some(1));
+        assert_eq!(outcome.carried_points, points(3));
     }
 
     #[test]
-    fn test_calculate_normal_hand_points_two_players() {
-        let mut signed_cards = vec![];
-        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
-                                     player_signature: 0});
-        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Spades},
-                                     player_signature: 1});
-        let hand_outcome = calculate_normal_hand_points(signed_cards);
-        assert_eq!(hand_outcome.earned_points.len(), 2);
-        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), 0);
-        assert_eq!(*hand_outcome.earned_points.get(&(1 as i32)).unwrap(), POINTS_MAX_CARD as i32);
-        assert_eq!(hand_outcome.max_card_points, 10);
-        assert_eq!(hand_outcome.players_with_max_card.len(), 1);
-        assert!(hand_outcome.players_with_max_card.contains(&1));
-        assert!(!hand_outcome.slowest_player.is_some());
-        assert!(!hand_outcome.fastest_player.is_some());
+    fn test_late_players_take_the_slowest_penalty() {
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 4, suit:CardSuit::Spades}, player_signature: 0},
+            SignedCard{card: Card{number: 9, suit:CardSuit::Hearts}, player_signature: 1},
+        ];
+        let outcome = determine_hand_outcome(signed_cards.clone(), false, points(0), &[2, 3], &RoundScorers::default());
+        assert_eq!(outcome.earned_points[&1], points(POINTS_MAX_CARD));
+        assert_eq!(outcome.earned_points[&2], points(POINTS_SLOWER_PLAYER));
+        assert_eq!(outcome.earned_points[&3], points(POINTS_SLOWER_PLAYER));
+        assert_eq!(outcome.slowest_player, Some(3));
+        assert_eq!(outcome.fastest_player, Some(0));
+
+        /* Un puntaje propio decide solo que hacer con los que llegan tarde. */
+        let scorers = RoundScorers{rustic: Box::new(LowestCardScorer), ..RoundScorers::default()};
+        let outcome = determine_hand_outcome(signed_cards, false, points(0), &[2, 3], &scorers);
+        assert_eq!(outcome.earned_points.len(), 1);
+        assert_eq!(outcome.slowest_player, None);
     }
 
     #[test]
-    fn test_calculate_normal_hand_points_two_winners() {
-        let mut signed_cards = vec![];
-        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
-                                     player_signature: 0});
-        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Hearts},
-                                     player_signature: 1});
-        signed_cards.push(SignedCard{card: Card{number: 1, suit:CardSuit::Diamonds},
-                                     player_signature: 2});
-        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Clubs},
-                                     player_signature: 3});
-        let hand_outcome = calculate_normal_hand_points(signed_cards);
-        assert_eq!(hand_outcome.earned_points.len(), 4);
-        assert_eq!(hand_outcome.max_card_points, POINTS_MAX_CARD/2);
-        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), 0);
-        assert_eq!(*hand_outcome.earned_points.get(&(1 as i32)).unwrap(), POINTS_MAX_CARD/2);
-        assert_eq!(*hand_outcome.earned_points.get(&(2 as i32)).unwrap(), 0);
-        assert_eq!(*hand_outcome.earned_points.get(&(3 as i32)).unwrap(), POINTS_MAX_CARD/2);
-        assert_eq!(hand_outcome.players_with_max_card.len(), 2);
-        assert!(hand_outcome.players_with_max_card.contains(&1));
-        assert!(hand_outcome.players_with_max_card.contains(&3));
-        assert!(!hand_outcome.slowest_player.is_some());
-        assert!(!hand_outcome.fastest_player.is_some());
+    fn test_player_missing_the_deadline_is_slowest() {
+        let ruleset = Ruleset{normal_round_probability: 0.0, rustic_deadline_ms: Some(250), ..Ruleset::default()};
+        let result = Game::builder(3).seed(2).ruleset(ruleset)
+            .seat_reaction_time(1, ReactionTime::Fixed{ms: 1000.0})
+            .build().unwrap().run().unwrap();
+        assert_eq!(result.rounds[0].late_players, vec![1]);
+        for round in &result.rounds {
+            if round.late_players.contains(&1) {
+                assert_eq!(round.hand_outcome.slowest_player, Some(1));
+            }
+            assert!(round.cards.iter().all(|signed_card| signed_card.player_signature != 1));
+        }
+    }
+
+    /* Tira en la primera ronda y despues se queda colgado hasta que se cierra el canal, asi el hilo
+    termina cuando el test suelta el otro extremo. */
+    struct StuckStrategy {
+        release: mpsc::Receiver<()>
+    }
+
+    impl PlayerStrategy for StuckStrategy {
+        fn choose_card(&mut self, _hand: &[Card], table: &TableView) -> usize {
+            if table.round > 1 {
+                let _ = self.release.recv();
+            }
+            return 0;
+        }
     }
 
     #[test]
-    fn test_calculate_rustic_hand_points_two_winners() {
-        let mut signed_cards = vec![];
-        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
-                                     player_signature: 1});
-        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Hearts},
-                                     player_signature: 2});
-        signed_cards.push(SignedCard{card: Card{number: 1, suit:CardSuit::Diamonds},
-                                     player_signature: 0});
-        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Clubs},
-                                     player_signature: 3});
-        let hand_outcome = calculate_rustic_hand_points(signed_cards);
-        assert_eq!(hand_outcome.earned_points.len(), 4);
-        assert_eq!(hand_outcome.max_card_points, POINTS_MAX_CARD/2);
-        assert!(hand_outcome.slowest_player.is_some());
-        assert!(hand_outcome.fastest_player.is_some());
-        assert_eq!(hand_outcome.slowest_player.unwrap(), 3);
-        assert_eq!(hand_outcome.fastest_player.unwrap(), 1);
-        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), 0);
-        assert_eq!(*hand_outcome.earned_points.get(&(1 as i32)).unwrap(), POINTS_FASTER_PLAYER);
-        assert_eq!(*hand_outcome.earned_points.get(&(2 as i32)).unwrap(), POINTS_MAX_CARD/2);
-        assert_eq!(*hand_outcome.earned_points.get(&(3 as i32)).unwrap(), POINTS_MAX_CARD/2 + POINTS_SLOWER_PLAYER);
-        assert_eq!(hand_outcome.players_with_max_card.len(), 2);
-        assert!(hand_outcome.players_with_max_card.contains(&2));
-        assert!(hand_outcome.players_with_max_card.contains(&3));
+    fn test_rounds_go_on_without_late_players() {
+        let ruleset = Ruleset{normal_round_probability: 0.0, rustic_deadline_ms: Some(250), ..Ruleset::default()};
+        let mut engines = vec![Engine::Threads];
+        if cfg!(feature = "async") {
+            engines.push(Engine::Async);
+        }
+        for engine in engines {
+            let (release, stuck) = mpsc::channel();
+            let start = Instant::now();
+            let result = Game::builder(4).seed(3).ruleset(ruleset).engine(engine)
+                .seat_reaction_time(1, ReactionTime::Fixed{ms: 1000.0})
+                .custom_strategy(2, Box::new(StuckStrategy{release: stuck}))
+                .build().unwrap().run().unwrap();
+            drop(release);
+            assert!(start.elapsed() < Duration::from_secs(3), "{:?} took {:?}", engine, start.elapsed());
+            let stuck_round = result.rounds.iter().position(|round| round.late_players.contains(&2)).unwrap();
+            assert_eq!(result.rounds.iter().filter(|round| round.late_players.contains(&2)).count(), 1);
+            for round in &result.rounds[stuck_round..] {
+                assert!(round.cards.iter().all(|signed_card| signed_card.player_signature != 2));
+            }
+            assert!(result.rounds.iter().all(|round| round.cards.iter().all(|signed_card| signed_card.player_signature != 1)));
+        }
     }
 }
@@ -0,0 +1,178 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use serde::{Deserialize, Serialize};
+
+use crate::cards::Card;
+use crate::game_result::RoundResult;
+use crate::logger::{LogFile, error};
+use crate::round_scorer::Points;
+use crate::strategy::{PlayerStrategy, TableView};
+
+/* Que se hace cuando un jugador entra en panico. Eliminate saca su asiento de la partida y el resto
+sigue jugando, Replace hace que una estrategia de respaldo siga jugando por el y Abort termina la
+partida al final de la ronda en curso con un error. Si quedan menos de dos jugadores la partida se
+termina igual que con Abort. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashPolicy {
+    #[default]
+    Eliminate,
+    Replace,
+    Abort
+}
+
+impl CrashPolicy {
+    pub fn description(&self) -> &'static str {
+        return match self {
+            CrashPolicy::Eliminate => "se elimina su asiento",
+            CrashPolicy::Replace => "lo reemplaza una estrategia de respaldo",
+            CrashPolicy::Abort => "se interrumpe la partida"
+        };
+    }
+}
+
+impl std::str::FromStr for CrashPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        return match policy.trim().to_lowercase().as_str() {
+            "eliminate" => Ok(CrashPolicy::Eliminate),
+            "replace" => Ok(CrashPolicy::Replace),
+            "abort" => Ok(CrashPolicy::Abort),
+            _ => Err(format!("Unknown crash policy '{}', expected eliminate, replace or abort.", policy))
+        };
+    }
+}
+
+/* Mensaje con el que se llamo a panic!, si es un texto. */
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+    return "unknown panic".to_string();
+}
+
+/* Estrategia que atrapa los panicos de la original y desde ese momento juega con la de respaldo. La
+de respaldo recibe todos los avisos de la partida para poder tomar la posta en cualquier ronda. */
+pub struct FallbackStrategy {
+    player_id: i32,
+    strategy: Box<dyn PlayerStrategy + Send>,
+    fallback: Box<dyn PlayerStrategy + Send>,
+    crashed: bool,
+    logfile: LogFile
+}
+
+impl FallbackStrategy {
+    pub fn new(player_id: i32, strategy: Box<dyn PlayerStrategy + Send>, fallback: Box<dyn PlayerStrategy + Send>,
+               logfile: LogFile) -> Self {
+        return FallbackStrategy{player_id, strategy, fallback, crashed: false, logfile};
+    }
+
+    /* Corre call con la estrategia original. None si ya habia fallado o falla ahora. */
+    fn guard<R, F: FnOnce(&mut (dyn PlayerStrategy + Send)) -> R>(&mut self, call: F) -> Option<R> {
+        if self.crashed {
+            return None;
+        }
+        let strategy = &mut self.strategy;
+        return match panic::catch_unwind(AssertUnwindSafe(|| call(strategy.as_mut()))) {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                self.crashed = true;
+                let _ = error(self.logfile.clone(), format!("La estrategia del jugador {} entró en pánico ({}), \
+                sigue jugando la estrategia de respaldo", self.player_id, panic_message(payload.as_ref())));
+                None
+            }
+        };
+    }
+}
+
+impl PlayerStrategy for FallbackStrategy {
+    fn choose_card(&mut self, hand: &[Card], table: &TableView) -> usize {
+        return match self.guard(|strategy| strategy.choose_card(hand, table)) {
+            Some(index) if index < hand.len() => index,
+            Some(index) => {
+                self.crashed = true;
+                let _ = error(self.logfile.clone(), format!("La estrategia del jugador {} eligió la carta {} de una mano \
+                de {}, sigue jugando la estrategia de respaldo", self.player_id, index, hand.len()));
+                self.fallback.choose_card(hand, table)
+            }
+            None => self.fallback.choose_card(hand, table)
+        };
+    }
+
+    fn deal(&mut self, player_id: i32, hand: &[Card]) {
+        self.fallback.deal(player_id, hand);
+        self.guard(|strategy| strategy.deal(player_id, hand));
+    }
+
+    fn round_result(&mut self, round: &RoundResult) {
+        self.fallback.round_result(round);
+        self.guard(|strategy| strategy.round_result(round));
+    }
+
+    fn game_over(&mut self, points_by_user: &HashMap<i32, Points>, winners: &[i32]) {
+        if self.guard(|strategy| strategy.game_over(points_by_user, winners)).is_none() {
+            self.fallback.game_over(points_by_user, winners);
+        }
+    }
+
+    fn description(&self) -> String {
+        return self.strategy.description();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::cards::CardSuit;
+    use crate::strategy::StrategyKind;
+    use crate::ruleset::Ruleset;
+
+    struct PanickingStrategy;
+
+    impl PlayerStrategy for PanickingStrategy {
+        fn choose_card(&mut self, _hand: &[Card], _table: &TableView) -> usize {
+            panic!("no card for you");
+        }
+    }
+
+    #[test]
+    fn test_fallback_takes_over_after_a_panic() {
+        let fallback = StrategyKind::LowestFirst.build(Ruleset::default(), 0);
+        let mut strategy = FallbackStrategy::new(0, Box::new(PanickingStrategy), fallback, Arc::new(Mutex::new(None)));
+        let hand = [Card{number: 9, suit: CardSuit::Clubs}, Card{number: 2, suit: CardSuit::Hearts}];
+        assert_eq!(strategy.choose_card(&hand, &TableView::default()), 1);
+        assert!(strategy.crashed);
+        assert_eq!(strategy.choose_card(&hand, &TableView::default()), 1);
+    }
+
+    struct OutOfRangeStrategy;
+
+    impl PlayerStrategy for OutOfRangeStrategy {
+        fn choose_card(&mut self, hand: &[Card], _table: &TableView) -> usize {
+            return hand.len();
+        }
+    }
+
+    #[test]
+    fn test_fallback_takes_over_after_an_invalid_card() {
+        let fallback = StrategyKind::LowestFirst.build(Ruleset::default(), 0);
+        let mut strategy = FallbackStrategy::new(0, Box::new(OutOfRangeStrategy), fallback, Arc::new(Mutex::new(None)));
+        let hand = [Card{number: 9, suit: CardSuit::Clubs}, Card{number: 2, suit: CardSuit::Hearts}];
+        assert_eq!(strategy.choose_card(&hand, &TableView::default()), 1);
+        assert!(strategy.crashed);
+    }
+
+    #[test]
+    fn test_parse_crash_policy() {
+        assert_eq!("Replace".parse(), Ok(CrashPolicy::Replace));
+        assert!("ignore".parse::<CrashPolicy>().is_err());
+        assert_eq!(panic_message(&"boom"), "boom");
+        assert_eq!(panic_message(&"boom".to_string()), "boom");
+    }
+}
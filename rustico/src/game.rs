@@ -0,0 +1,656 @@
+use std::sync::{Arc, Mutex};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::collections::HashMap;
+use std::io::Error;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::player::player;
+use crate::remote::{relay, RemoteSeat};
+use crate::coordinator::{coordinator, GameSettings};
+use crate::logger::{LogFile, info};
+use crate::round_scorer::RoundScorers;
+use crate::ruleset::Ruleset;
+use crate::game_result::GameResult;
+use crate::strategy::{PlayerStrategy, StrategyKind};
+use crate::transport::{block_on, in_process, PlayerTransport};
+use crate::sequential::{ArrivalModel, ArrivalOrder};
+use crate::reaction::{ReactionTime, Reflexes};
+use crate::crash::{panic_message, CrashPolicy, FallbackStrategy};
+
+/* Se puede jugar con cualquier cantidad de jugadores a partir de dos; la paridad no cambia ninguna
+regla. */
+pub const MIN_PLAYERS :i32 = 2;
+
+fn check_player_quantity(players: i32) -> bool {
+    return players >= MIN_PLAYERS
+}
+
+pub fn validate_game(players: i32, ruleset: &Ruleset) -> Result<(), String> {
+    if !check_player_quantity(players) {
+        return Err(format!("Number of players should be greater or equal than {}.", MIN_PLAYERS));
+    }
+    return ruleset.validate(players);
+}
+
+/* Como se ejecuta la partida: un hilo por jugador, una tarea de tokio por jugador si se compila con
+la feature async, o todo en un solo hilo con el orden de llegada de las rondas rusticas simulado.
+Todas puntuan igual. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    #[default]
+    Threads,
+    Async,
+    Sequential
+}
+
+impl Engine {
+    pub fn description(&self) -> &'static str {
+        return match self {
+            Engine::Threads => "un hilo por jugador",
+            Engine::Async => "tareas asincronicas",
+            Engine::Sequential => "secuencial"
+        };
+    }
+}
+
+impl std::str::FromStr for Engine {
+    type Err = String;
+
+    fn from_str(engine: &str) -> Result<Self, Self::Err> {
+        return match engine.trim().to_lowercase().as_str() {
+            "threads" => Ok(Engine::Threads),
+            "async" => Ok(Engine::Async),
+            "sequential" => Ok(Engine::Sequential),
+            _ => Err(format!("Unknown engine '{}', expected threads, async or sequential.", engine))
+        };
+    }
+}
+
+/* Quien juega un asiento: una estrategia en este proceso o un cliente conectado que corre su propio
+jugador. */
+pub(crate) enum Seat {
+    Local(Box<dyn PlayerStrategy + Send>),
+    Remote(RemoteSeat)
+}
+
+impl Seat {
+    /* Juega el asiento con el transporte del motor. */
+    pub(crate) async fn play<T: PlayerTransport>(self, log: LogFile, transport: T, player_id: i32,
+                                                 reflexes: Reflexes) -> std::io::Result<()> {
+        return match self {
+            Seat::Local(strategy) => player(log, transport, player_id, strategy, reflexes).await,
+            Seat::Remote(remote) => relay(log, transport, remote).await
+        };
+    }
+}
+
+/* El motor secuencial no tiene transporte, asi que GameBuilder no le da asientos remotos. */
+fn local_strategies(seats: Vec<Seat>) -> Vec<Box<dyn PlayerStrategy + Send>> {
+    return seats.into_iter()
+        .map(|seat| match seat {
+            Seat::Local(strategy) => strategy,
+            Seat::Remote(_) => unreachable!("GameBuilder only seats remote players in the threads and async engines")
+        })
+        .collect();
+}
+
+/* Partida lista para jugarse. Se construye con un GameBuilder, que valida los parametros antes de
+lanzar cualquier hilo. */
+pub struct Game {
+    settings: GameSettings,
+    seats: Vec<Seat>,
+    reflexes: Vec<Reflexes>,
+    engine: Engine,
+    arrival: Box<dyn ArrivalOrder + Send>,
+    logfile: LogFile
+}
+
+pub struct GameBuilder {
+    players: i32,
+    seed: Option<u64>,
+    ruleset: Ruleset,
+    scorers: Option<RoundScorers>,
+    strategy: StrategyKind,
+    seat_strategies: HashMap<i32, StrategyKind>,
+    custom_strategies: HashMap<i32, Box<dyn PlayerStrategy + Send>>,
+    remote_seats: HashMap<i32, RemoteSeat>,
+    reaction_time: ReactionTime,
+    seat_reaction_times: HashMap<i32, ReactionTime>,
+    engine: Engine,
+    arrival: ArrivalModel,
+    custom_arrival: Option<Box<dyn ArrivalOrder + Send>>,
+    crash_policy: CrashPolicy,
+    logfile: LogFile
+}
+
+impl GameBuilder {
+    pub fn new(players: i32) -> Self {
+        return GameBuilder{players, seed: None, ruleset: Ruleset::default(), scorers: None,
+                           strategy: StrategyKind::default(), seat_strategies: HashMap::new(),
+                           custom_strategies: HashMap::new(), remote_seats: HashMap::new(), reaction_time: ReactionTime::default(),
+                           seat_reaction_times: HashMap::new(), engine: Engine::default(),
+                           arrival: ArrivalModel::default(), custom_arrival: None,
+                           crash_policy: CrashPolicy::default(), logfile: Arc::new(Mutex::new(None))};
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        return self;
+    }
+
+    pub fn ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+        return self;
+    }
+
+    /* Reemplaza las reglas de puntuacion construidas a partir del ruleset. */
+    pub fn scorers(mut self, scorers: RoundScorers) -> Self {
+        self.scorers = Some(scorers);
+        return self;
+    }
+
+    /* Estrategia de los asientos que no tienen una propia. */
+    pub fn strategy(mut self, strategy: StrategyKind) -> Self {
+        self.strategy = strategy;
+        return self;
+    }
+
+    pub fn seat_strategy(mut self, seat: i32, strategy: StrategyKind) -> Self {
+        self.seat_strategies.insert(seat, strategy);
+        return self;
+    }
+
+    /* Estrategia propia para un asiento; tiene prioridad sobre las elegidas con strategy y
+    seat_strategy. */
+    pub fn custom_strategy(mut self, seat: i32, strategy: Box<dyn PlayerStrategy + Send>) -> Self {
+        self.custom_strategies.insert(seat, strategy);
+        return self;
+    }
+
+    /* Asiento jugado por un cliente conectado, que elige sus cartas y sus tiempos de reaccion; tiene
+    prioridad sobre cualquier estrategia. Solo lo juegan los motores con hilos y con tareas. */
+    pub fn remote_seat(mut self, remote: RemoteSeat) -> Self {
+        self.remote_seats.insert(remote.seat(), remote);
+        return self;
+    }
+
+    /* Tiempo de reaccion en las rondas rusticas de los asientos que no tienen uno propio. */
+    pub fn reaction_time(mut self, reaction_time: ReactionTime) -> Self {
+        self.reaction_time = reaction_time;
+        return self;
+    }
+
+    pub fn seat_reaction_time(mut self, seat: i32, reaction_time: ReactionTime) -> Self {
+        self.seat_reaction_times.insert(seat, reaction_time);
+        return self;
+    }
+
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        return self;
+    }
+
+    /* Orden de llegada de las rondas rusticas en el motor secuencial. */
+    pub fn arrival(mut self, arrival: ArrivalModel) -> Self {
+        self.arrival = arrival;
+        return self;
+    }
+
+    /* Orden de llegada propio; tiene prioridad sobre el elegido con arrival. */
+    pub fn custom_arrival(mut self, arrival: Box<dyn ArrivalOrder + Send>) -> Self {
+        self.custom_arrival = Some(arrival);
+        return self;
+    }
+
+    /* Que hacer si un jugador entra en panico. */
+    pub fn on_crash(mut self, crash_policy: CrashPolicy) -> Self {
+        self.crash_policy = crash_policy;
+        return self;
+    }
+
+    pub fn logfile(mut self, logfile: LogFile) -> Self {
+        self.logfile = logfile;
+        return self;
+    }
+
+    pub fn build(mut self) -> Result<Game, String> {
+        validate_game(self.players, &self.ruleset)?;
+        if self.engine == Engine::Async && !cfg!(feature = "async") {
+            return Err("The async engine needs rustico to be built with the async feature.".to_string());
+        }
+        if self.engine == Engine::Sequential && !self.remote_seats.is_empty() {
+            return Err("Remote seats need the threads or async engine.".to_string());
+        }
+        if let Some(seat) = self.custom_strategies.keys().filter(|seat| self.remote_seats.contains_key(*seat)).min() {
+            return Err(format!("Seat {} has both a custom strategy and a remote player.", seat));
+        }
+        let seats = self.seat_strategies.keys().chain(self.custom_strategies.keys()).chain(self.remote_seats.keys());
+        if let Some(seat) = seats.filter(|seat| !(0..self.players).contains(*seat)).min() {
+            return Err(format!("Strategy given for seat {}, but seats go from 0 to {}.", seat, self.players - 1));
+        }
+        if let Some(seat) = self.seat_reaction_times.keys().filter(|seat| !(0..self.players).contains(*seat)).min() {
+            return Err(format!("Reaction time given for seat {}, but seats go from 0 to {}.", seat, self.players - 1));
+        }
+        self.reaction_time.validate()?;
+        for seat in 0..self.players {
+            if let Some(reaction_time) = self.seat_reaction_times.get(&seat) {
+                reaction_time.validate().map_err(|e| format!("Seat {}: {}", seat, e))?;
+            }
+        }
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let ruleset = self.ruleset;
+        /* Cada asiento con estrategia al azar usa su propia semilla, derivada de la de la partida. */
+        let mut seats: Vec<Seat> = (0..self.players)
+            .map(|seat| match (self.remote_seats.remove(&seat), self.custom_strategies.remove(&seat)) {
+                (Some(remote), _) => Seat::Remote(remote),
+                (None, Some(strategy)) => Seat::Local(strategy),
+                (None, None) => Seat::Local(self.seat_strategies.get(&seat).unwrap_or(&self.strategy)
+                    .build(ruleset, seed.wrapping_add(seat as u64 + 1)))
+            })
+            .collect();
+        /* Los asientos remotos no se reemplazan: si se caen quedan fuera de la partida. */
+        if self.crash_policy == CrashPolicy::Replace {
+            let logfile = self.logfile.clone();
+            seats = seats.into_iter().enumerate()
+                .map(|(seat, occupant)| match occupant {
+                    Seat::Local(strategy) => {
+                        let fallback = StrategyKind::default().build(ruleset, seed.wrapping_add(seat as u64 + 1));
+                        Seat::Local(Box::new(FallbackStrategy::new(seat as i32, strategy, fallback, logfile.clone())))
+                    },
+                    remote => remote
+                })
+                .collect();
+        }
+        /* Los tiempos de reaccion y el orden de llegada usan semillas distintas a las de las estrategias. */
+        let reflexes = (0..self.players)
+            .map(|seat| Reflexes::new(*self.seat_reaction_times.get(&seat).unwrap_or(&self.reaction_time),
+                                      seed.rotate_left(32).wrapping_add(seat as u64 + 1)))
+            .collect();
+        let arrival_model = self.arrival;
+        let arrival = self.custom_arrival.unwrap_or_else(|| arrival_model.build(seed.wrapping_sub(1)));
+        let scorers = self.scorers.unwrap_or_else(|| RoundScorers::new(ruleset));
+        let settings = GameSettings{players: self.players, seed, ruleset, scorers, crash_policy: self.crash_policy};
+        return Ok(Game{settings, seats, reflexes, engine: self.engine, arrival,
+                       logfile: self.logfile});
+    }
+}
+
+impl Game {
+    pub fn builder(players: i32) -> GameBuilder {
+        return GameBuilder::new(players);
+    }
+
+    pub fn seed(&self) -> u64 {
+        return self.settings.seed;
+    }
+
+    pub fn run(self) -> std::io::Result<GameResult> {
+        info(self.logfile.clone(), format!("Motor de juego: {}", self.engine.description()))?;
+        info(self.logfile.clone(), format!("Si un jugador se cae {}", self.settings.crash_policy.description()))?;
+        info(self.logfile.clone(), "Esperando jugadores".to_string())?;
+        for (p, (seat, reflexes)) in self.seats.iter().zip(&self.reflexes).enumerate() {
+            let strategy = match seat {
+                Seat::Local(strategy) => strategy,
+                Seat::Remote(remote) => {
+                    info(self.logfile.clone(), format!("El jugador {} es remoto ({})", p, remote.name()))?;
+                    continue;
+                }
+            };
+            info(self.logfile.clone(), format!("El jugador {} juega con la estrategia {}", p, strategy.description()))?;
+            if reflexes.reaction_time() != ReactionTime::Immediate {
+                info(self.logfile.clone(), format!("El jugador {} tiene un tiempo de reaccion {}", p,
+                                                   reflexes.reaction_time().description()))?;
+            }
+        }
+        return match self.engine {
+            Engine::Threads => set_up_threads(self.settings, self.seats, self.reflexes, self.logfile),
+            #[cfg(feature = "async")]
+            Engine::Async => crate::async_engine::run(self.settings, self.seats, self.reflexes, self.logfile),
+            #[cfg(not(feature = "async"))]
+            Engine::Async => unreachable!("GameBuilder rejects the async engine without the async feature"),
+            Engine::Sequential => crate::sequential::run(self.settings, local_strategies(self.seats), self.reflexes,
+                                                         self.arrival, self.logfile)
+        };
+    }
+}
+
+fn set_up_threads(settings: GameSettings, seats: Vec<Seat>,
+                  reflexes: Vec<Reflexes>, log_file: LogFile) -> std::io::Result<GameResult> {
+    let (coordinator_transport, player_transports) = in_process(settings.players);
+    let barrier = coordinator_transport.barrier();
+
+    let mut threads = Vec::new();
+
+    /* Si un jugador entra en panico o termina con un error se lo saca de la barrera y se le avisa al
+    coordinador, que decide segun la politica de la partida. */
+    let seats = seats.into_iter().zip(reflexes);
+    for (p, (transport, (seat, reflexes))) in player_transports.into_iter().zip(seats).enumerate() {
+        let log_file_clone = log_file.clone();
+        let crash_reporter = transport.crash_reporter();
+        threads.push(thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                return block_on(seat.play(log_file_clone, transport, p as i32, reflexes));
+            }));
+            let reason = match result {
+                Ok(Ok(())) => return,
+                Ok(Err(e)) => e.to_string(),
+                Err(payload) => panic_message(payload.as_ref())
+            };
+            crash_reporter.report(p as i32, reason);
+        }));
+    }
+
+    info(log_file.clone(), "Iniciando coordinador".to_string())?;
+    let coordinator_thread = thread::spawn(move || {
+        return block_on(coordinator(log_file, settings, coordinator_transport));
+    });
+
+    let game_result = coordinator_thread.join()
+        .map_err(|_| Error::other("The coordinator thread panicked."))?;
+
+    /* Los que nunca volvieron de su ultima ronda terminan solos cuando puedan. */
+    for (p, thread) in threads.into_iter().enumerate() {
+        if !barrier.is_excused(p as i32) {
+            thread.join().unwrap();
+        }
+    }
+
+    return game_result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::round_scorer::Points;
+    use crate::cards::Card;
+    use crate::game_result::RoundType;
+    use crate::strategy::TableView;
+    use crate::crash::CrashPolicy;
+    use crate::cards::{DeckBuilder, DeckKind};
+    use crate::coordinator::LatePolicy;
+
+    #[test]
+    fn test_invalid_games_are_not_built() {
+        assert!(Game::builder(1).build().is_err());
+        assert!(Game::builder(0).build().is_err());
+        let ruleset = Ruleset{suspension_rounds: 6, ..Ruleset::default()};
+        assert!(Game::builder(6).ruleset(ruleset).build().is_err());
+    }
+
+    #[test]
+    fn test_run_game() {
+        let game = Game::builder(4).seed(11).build().unwrap();
+        assert_eq!(game.seed(), 11);
+        let result = game.run().unwrap();
+        assert_eq!(result.seed, 11);
+        assert_eq!(result.points_by_user.len(), 4);
+        assert_eq!(result.round_count as usize, result.rounds.len());
+        assert_eq!(result.ranking.len(), 4);
+        assert_eq!(result.ranking[0], result.winners[0]);
+        for points in result.points_by_user.values() {
+            assert!(*points <= result.winner_points());
+        }
+        let total_points: Points = result.points_by_user.values().sum();
+        let rounds_points: Points = result.rounds.iter()
+            .map(|round| round.hand_outcome.earned_points.values().sum::<Points>()).sum();
+        assert_eq!(total_points, rounds_points);
+        for (previous, round) in result.rounds.iter().zip(result.rounds.iter().skip(1)) {
+            if let Some(slowest_player) = previous.hand_outcome.slowest_player {
+                assert_eq!(round.suspended_players, vec![slowest_player]);
+            }
+        }
+    }
+
+    struct RecordingStrategy {
+        hand_sizes: Arc<Mutex<Vec<usize>>>,
+        tables: Arc<Mutex<Vec<TableView>>>
+    }
+
+    impl RecordingStrategy {
+        fn new() -> Self {
+            return RecordingStrategy{hand_sizes: Arc::new(Mutex::new(Vec::new())),
+                                     tables: Arc::new(Mutex::new(Vec::new()))};
+        }
+    }
+
+    impl PlayerStrategy for RecordingStrategy {
+        fn choose_card(&mut self, hand: &[Card], table: &TableView) -> usize {
+            self.hand_sizes.lock().unwrap().push(hand.len());
+            self.tables.lock().unwrap().push(table.clone());
+            return hand.len() - 1;
+        }
+    }
+
+    #[test]
+    fn test_seat_strategies() {
+        let recording = RecordingStrategy::new();
+        let hand_sizes = recording.hand_sizes.clone();
+        let result = Game::builder(4).seed(2).strategy(StrategyKind::Random)
+            .seat_strategy(0, StrategyKind::HighestFirst)
+            .custom_strategy(1, Box::new(recording))
+            .build().unwrap().run().unwrap();
+        let thrown = result.rounds.iter()
+            .filter(|round| round.cards.iter().any(|signed_card| signed_card.player_signature == 1))
+            .count();
+        let hand_sizes = hand_sizes.lock().unwrap();
+        assert_eq!(hand_sizes.len(), thrown);
+        assert_eq!(hand_sizes[0], 13);
+        assert!(hand_sizes.windows(2).all(|sizes| sizes[1] == sizes[0] - 1));
+        assert!(Game::builder(4).seat_strategy(4, StrategyKind::Random).build().is_err());
+        assert!(Game::builder(4).custom_strategy(-1, StrategyKind::Random.build(Ruleset::default(), 1)).build().is_err());
+    }
+
+    #[test]
+    fn test_strategies_see_the_table() {
+        let recording = RecordingStrategy::new();
+        let tables = recording.tables.clone();
+        let result = Game::builder(4).seed(8).custom_strategy(2, Box::new(recording))
+            .build().unwrap().run().unwrap();
+        let tables = tables.lock().unwrap();
+        let mut points_by_user: HashMap<i32, Points> = (0..4).map(|p| (p, Points::from_integer(0))).collect();
+        let mut seen = 0;
+        for round in &result.rounds {
+            if round.cards.iter().any(|signed_card| signed_card.player_signature == 2) {
+                let table = &tables[seen];
+                assert_eq!(table.player_id, 2);
+                assert_eq!(table.cards_thrown, seen as i32);
+                assert_eq!(table.round, round.round);
+                assert_eq!(table.round_type, round.round_type);
+                assert_eq!(table.suspended_players, round.suspended_players);
+                assert_eq!(table.points_by_user, points_by_user);
+                let expected_cards = match round.round_type {
+                    RoundType::Normal => round.cards.iter().take_while(|card| card.player_signature < 2).copied().collect(),
+                    RoundType::Rustic => vec![]
+                };
+                assert_eq!(table.cards_played, expected_cards);
+                seen += 1;
+            }
+            for (player, points) in &round.hand_outcome.earned_points {
+                *points_by_user.get_mut(player).unwrap() += points;
+            }
+        }
+        assert_eq!(seen, tables.len());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_engine_scores_like_threads() {
+        /* Sin rondas rusticas no influye el orden de llegada, asi que las dos partidas son iguales. */
+        let ruleset = Ruleset{normal_round_probability: 1.0, ..Ruleset::default()};
+        let play = |engine| Game::builder(5).seed(9).ruleset(ruleset).strategy(StrategyKind::Random)
+            .engine(engine).build().unwrap().run().unwrap();
+        let threads = play(Engine::Threads);
+        let tasks = play(Engine::Async);
+        assert_eq!(tasks.points_by_user, threads.points_by_user);
+        assert_eq!(tasks.winners, threads.winners);
+        assert_eq!(tasks.rounds.len(), threads.rounds.len());
+        for (task_round, thread_round) in tasks.rounds.iter().zip(&threads.rounds) {
+            assert_eq!(task_round.cards, thread_round.cards);
+        }
+        let rustic = Game::builder(300).seed(9).engine(Engine::Async).build().unwrap().run().unwrap();
+        assert_eq!(rustic.points_by_user.len(), 300);
+    }
+
+    /* Juega bien hasta la ronda indicada y ahi entra en panico. */
+    struct CrashingStrategy {
+        round: i32
+    }
+
+    impl PlayerStrategy for CrashingStrategy {
+        fn choose_card(&mut self, _hand: &[Card], table: &TableView) -> usize {
+            if table.round >= self.round {
+                panic!("the strategy broke");
+            }
+            return 0;
+        }
+    }
+
+    struct InvalidCardStrategy {
+        round: i32
+    }
+
+    impl PlayerStrategy for InvalidCardStrategy {
+        fn choose_card(&mut self, hand: &[Card], table: &TableView) -> usize {
+            return if table.round >= self.round { hand.len() } else { 0 };
+        }
+    }
+
+    fn engines() -> Vec<Engine> {
+        let mut engines = vec![Engine::Threads, Engine::Sequential];
+        if cfg!(feature = "async") {
+            engines.push(Engine::Async);
+        }
+        return engines;
+    }
+
+    #[test]
+    fn test_engines_score_rustic_rounds_alike() {
+        /* Con tiempos de reaccion bien separados el orden de llegada no depende del planificador. */
+        let ruleset = Ruleset{normal_round_probability: 0.5, deck: DeckBuilder::new(DeckKind::Spanish40),
+                              ..Ruleset::default()};
+        let play = |engine| Game::builder(3).seed(9).ruleset(ruleset).strategy(StrategyKind::Random)
+            .seat_reaction_time(1, ReactionTime::Fixed{ms: 100.0})
+            .seat_reaction_time(2, ReactionTime::Fixed{ms: 200.0})
+            .engine(engine).build().unwrap().run().unwrap();
+        let sequential = play(Engine::Sequential);
+        assert!(sequential.rounds.iter().any(|round| round.round_type == RoundType::Rustic));
+        for engine in engines() {
+            let result = play(engine);
+            assert_eq!(result.points_by_user, sequential.points_by_user, "{:?}", engine);
+            assert_eq!(result.rounds.len(), sequential.rounds.len(), "{:?}", engine);
+            for (round, sequential_round) in result.rounds.iter().zip(&sequential.rounds) {
+                assert_eq!(round.cards, sequential_round.cards, "{:?}", engine);
+            }
+        }
+    }
+
+    #[test]
+    fn test_engines_agree_with_a_rustic_deadline() {
+        /* El jugador 4 llega tarde a cada ronda que juega y termina de reaccionar a mitad de la
+        siguiente, a 90 ms de su principio y de su final: en todos los motores se la pierde y vuelve
+        en la otra. */
+        let ruleset = Ruleset{normal_round_probability: 0.0, suspension_rounds: 0, rustic_deadline_ms: Some(300),
+                              late_policy: LatePolicy::Forfeit, deck: DeckBuilder::new(DeckKind::Spanish40),
+                              ..Ruleset::default()};
+        let play = |engine| Game::builder(5).seed(9).ruleset(ruleset).strategy(StrategyKind::Random)
+            .seat_reaction_time(1, ReactionTime::Fixed{ms: 60.0})
+            .seat_reaction_time(2, ReactionTime::Fixed{ms: 120.0})
+            .seat_reaction_time(3, ReactionTime::Fixed{ms: 180.0})
+            .seat_reaction_time(4, ReactionTime::Fixed{ms: 390.0})
+            .engine(engine).build().unwrap().run().unwrap();
+        let sequential = play(Engine::Sequential);
+        assert!(sequential.rounds.iter().any(|round| round.late_players == vec![4]));
+        assert!(sequential.rounds.iter().any(|round| round.late_players.is_empty()));
+        for engine in engines() {
+            let result = play(engine);
+            assert_eq!(result.points_by_user, sequential.points_by_user, "{:?}", engine);
+            assert_eq!(result.rounds.len(), sequential.rounds.len(), "{:?}", engine);
+            for (round, sequential_round) in result.rounds.iter().zip(&sequential.rounds) {
+                assert_eq!(round.cards, sequential_round.cards, "{:?}", engine);
+                assert_eq!(round.late_players, sequential_round.late_players, "{:?}", engine);
+            }
+        }
+    }
+
+    fn crashing_game(players: i32, engine: Engine, crash_policy: CrashPolicy) -> std::io::Result<GameResult> {
+        return Game::builder(players).seed(4).engine(engine).on_crash(crash_policy)
+            .custom_strategy(1, Box::new(CrashingStrategy{round: 3}))
+            .build().unwrap().run();
+    }
+
+    #[test]
+    fn test_crashed_player_is_eliminated() {
+        for engine in engines() {
+            let result = crashing_game(4, engine, CrashPolicy::Eliminate).unwrap();
+            for round in &result.rounds[2..] {
+                assert!(round.cards.iter().all(|signed_card| signed_card.player_signature != 1));
+                assert!(!round.late_players.contains(&1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_crashed_player_is_replaced() {
+        for engine in engines() {
+            let result = crashing_game(4, engine, CrashPolicy::Replace).unwrap();
+            for round in result.rounds.iter().filter(|round| !round.suspended_players.contains(&1)) {
+                assert!(round.cards.iter().any(|signed_card| signed_card.player_signature == 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_card_counts_as_a_crash() {
+        for engine in engines() {
+            let game = |crash_policy| Game::builder(4).seed(4).engine(engine).on_crash(crash_policy)
+                .custom_strategy(1, Box::new(InvalidCardStrategy{round: 3}))
+                .build().unwrap().run().unwrap();
+            let result = game(CrashPolicy::Eliminate);
+            assert!(result.rounds[2..].iter().all(|round| round.cards.iter().all(|signed_card| signed_card.player_signature != 1)));
+            let result = game(CrashPolicy::Replace);
+            for round in result.rounds.iter().filter(|round| !round.suspended_players.contains(&1)) {
+                assert!(round.cards.iter().any(|signed_card| signed_card.player_signature == 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_crash_aborts_the_game() {
+        for engine in engines() {
+            let error = crashing_game(4, engine, CrashPolicy::Abort).unwrap_err();
+            assert!(error.to_string().contains("Player 1 crashed (the strategy broke)"), "{}", error);
+            let error = crashing_game(2, engine, CrashPolicy::Eliminate).unwrap_err();
+            assert!(error.to_string().contains("only 1 players are left"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_reaction_times_decide_rustic_rounds() {
+        let ruleset = Ruleset{normal_round_probability: 0.0, deck: DeckBuilder::new(DeckKind::Spanish40),
+                              ..Ruleset::default()};
+        let result = Game::builder(3).seed(6).ruleset(ruleset)
+            .seat_reaction_time(1, ReactionTime::Fixed{ms: 100.0})
+            .seat_reaction_time(2, ReactionTime::Fixed{ms: 200.0})
+            .build().unwrap().run().unwrap();
+        for round in result.rounds.iter().filter(|round| round.cards.len() > 1) {
+            let throwers: Vec<i32> = round.cards.iter().map(|signed_card| signed_card.player_signature).collect();
+            assert_eq!(round.hand_outcome.fastest_player, throwers.iter().min().copied());
+            assert_eq!(round.hand_outcome.slowest_player, throwers.iter().max().copied());
+        }
+        assert!(Game::builder(3).seat_reaction_time(3, ReactionTime::Immediate).build().is_err());
+        assert!(Game::builder(3).reaction_time(ReactionTime::Fixed{ms: f64::NAN}).build().is_err());
+        assert!(Game::builder(3).seat_reaction_time(1, ReactionTime::Normal{mean_ms: 10.0, std_dev_ms: -5.0}).build().is_err());
+    }
+
+    #[test]
+    fn test_run_game_with_any_player_count() {
+        for players in [2, 3, 5, 53].iter() {
+            let result = Game::builder(*players).seed(7).build().unwrap().run().unwrap();
+            assert_eq!(result.points_by_user.len(), *players as usize);
+            assert!(result.round_count >= 1);
+        }
+    }
+}
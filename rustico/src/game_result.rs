@@ -0,0 +1,219 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::BufWriter;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::round_scorer::{HandOutcome, Points};
+use crate::signed_card::SignedCard;
+use crate::tiebreak::TiebreakPolicy;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundType {
+    #[default]
+    Normal,
+    Rustic
+}
+
+/* Lo ocurrido en una ronda: su tipo, las cartas en el orden en que llegaron al coordinador, los puntos
+de la mano, los jugadores que no tiraron carta por estar suspendidos y los que no la tiraron antes del
+tiempo limite. */
+#[derive(Clone, Debug, Serialize)]
+pub struct RoundResult {
+    pub round: i32,
+    pub round_type: RoundType,
+    pub cards: Vec<SignedCard>,
+    pub hand_outcome: HandOutcome,
+    pub suspended_players: Vec<i32>,
+    pub late_players: Vec<i32>
+}
+
+/* Resultado de una partida completa, tal como lo devuelve el coordinador. */
+#[derive(Clone, Debug)]
+pub struct GameResult {
+    pub seed: u64,
+    pub points_by_user: HashMap<i32, Points>,
+    pub ranking: Vec<i32>,
+    pub winners: Vec<i32>,
+    pub decided_by: Option<TiebreakPolicy>,
+    pub round_count: i32,
+    pub rounds: Vec<RoundResult>,
+    /* Jugadores que se cayeron y quedaron fuera de la partida, en el orden en que se cayeron. */
+    pub eliminated_players: Vec<i32>
+}
+
+#[derive(Serialize)]
+struct Standing {
+    player: i32,
+    #[serde(serialize_with = "serialize_points")]
+    points: Points,
+    suspensions: i32,
+    eliminated: bool
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    seed: u64,
+    winners: &'a Vec<i32>,
+    decided_by: Option<TiebreakPolicy>,
+    round_count: i32,
+    eliminated_players: &'a Vec<i32>,
+    standings: Vec<Standing>,
+    rounds: &'a Vec<RoundResult>
+}
+
+fn points_to_f64(points: &Points) -> f64 {
+    return *points.numer() as f64 / *points.denom() as f64;
+}
+
+/* Los puntos se escriben como numeros decimales, que es lo que esperan las herramientas que leen el
+JSON. */
+pub fn serialize_points<S: Serializer>(points: &Points, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_f64(points_to_f64(points));
+}
+
+/* Los mapas y conjuntos se escriben ordenados por id de jugador para que dos corridas con la misma
+semilla produzcan exactamente el mismo JSON. */
+pub fn serialize_sorted_map<S: Serializer>(map: &HashMap<i32, Points>, serializer: S) -> Result<S::Ok, S::Error> {
+    return map.iter().map(|(player, points)| (*player, points_to_f64(points)))
+        .collect::<BTreeMap<_, _>>().serialize(serializer);
+}
+
+pub fn serialize_sorted_set<S: Serializer>(set: &HashSet<i32>, serializer: S) -> Result<S::Ok, S::Error> {
+    return set.iter().collect::<BTreeSet<_>>().serialize(serializer);
+}
+
+/* Ordena a los jugadores de mayor a menor puntaje, con los ganadores del desempate al frente. A igual
+puntaje queda primero el de menor id. */
+pub fn rank_players(points_by_user: &HashMap<i32, Points>, winners: &[i32]) -> Vec<i32> {
+    let mut ranking: Vec<i32> = points_by_user.keys().copied().collect();
+    ranking.sort_by_key(|player| (!winners.contains(player), -points_by_user.get(player).unwrap(), *player));
+    return ranking;
+}
+
+impl GameResult {
+    pub fn new(seed: u64, points_by_user: HashMap<i32, Points>, rounds: Vec<RoundResult>, winners: Vec<i32>,
+               decided_by: Option<TiebreakPolicy>, eliminated_players: Vec<i32>) -> Self {
+        /* Los eliminados van al final del ranking, sin importar sus puntos. */
+        let mut ranking = rank_players(&points_by_user, &winners);
+        ranking.sort_by_key(|player| eliminated_players.contains(player));
+        let round_count = rounds.len() as i32;
+        return GameResult{seed, points_by_user, ranking, winners, decided_by, round_count, rounds, eliminated_players};
+    }
+
+    pub fn winner_points(&self) -> Points {
+        return *self.points_by_user.get(&self.winners[0]).unwrap();
+    }
+
+    /* Cantidad de rondas que cada jugador paso suspendido. */
+    pub fn suspensions_by_user(&self) -> HashMap<i32, i32> {
+        let mut suspensions: HashMap<i32, i32> = self.points_by_user.keys().map(|player| (*player, 0)).collect();
+        for round in &self.rounds {
+            for player in &round.suspended_players {
+                *suspensions.entry(*player).or_insert(0) += 1;
+            }
+        }
+        return suspensions;
+    }
+
+    fn json_report(&self) -> JsonReport<'_> {
+        let suspensions = self.suspensions_by_user();
+        let standings = self.ranking.iter()
+            .map(|player| Standing{player: *player, points: *self.points_by_user.get(player).unwrap(),
+                                   suspensions: *suspensions.get(player).unwrap(),
+                                   eliminated: self.eliminated_players.contains(player)})
+            .collect();
+        return JsonReport{seed: self.seed, winners: &self.winners, decided_by: self.decided_by,
+                          round_count: self.round_count, eliminated_players: &self.eliminated_players, standings,
+                          rounds: &self.rounds};
+    }
+
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(&self.json_report()).unwrap();
+    }
+
+    pub fn write_json(&self, path: &str) -> std::io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, &self.json_report())?;
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{Card, CardSuit};
+    use crate::round_scorer::points;
+
+    fn rustic_round(round: i32, slowest_player: i32, suspended_players: Vec<i32>) -> RoundResult {
+        let hand_outcome = HandOutcome{earned_points: HashMap::new(), max_card_points: points(0),
+                                       carried_points: points(0), players_with_max_card: HashSet::new(), slowest_player: Some(slowest_player),
+                                       fastest_player: None};
+        let cards = vec![SignedCard{card: Card{number: 3, suit: CardSuit::Hearts}, player_signature: slowest_player}];
+        return RoundResult{round, round_type: RoundType::Rustic, cards, hand_outcome, suspended_players,
+                           late_players: vec![]};
+    }
+
+    #[test]
+    fn test_ranking_orders_by_points_then_id() {
+        let mut points_by_user = HashMap::new();
+        points_by_user.insert(0, points(5));
+        points_by_user.insert(1, points(20));
+        points_by_user.insert(2, points(-3));
+        points_by_user.insert(3, points(20));
+        assert_eq!(rank_players(&points_by_user, &[1, 3]), vec![1, 3, 0, 2]);
+        assert_eq!(rank_players(&points_by_user, &[3]), vec![3, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_game_result_summary() {
+        let mut points_by_user = HashMap::new();
+        points_by_user.insert(0, points(5));
+        points_by_user.insert(1, points(7));
+        let rounds = vec![rustic_round(1, 0, vec![]), rustic_round(2, 1, vec![0]), rustic_round(3, 1, vec![1])];
+        let result = GameResult::new(3, points_by_user, rounds, vec![1], None, vec![]);
+        assert_eq!(result.winners, vec![1]);
+        assert_eq!(result.winner_points(), points(7));
+        assert_eq!(result.ranking, vec![1, 0]);
+        assert_eq!(result.round_count, 3);
+        assert_eq!(*result.suspensions_by_user().get(&0).unwrap(), 1);
+        assert_eq!(*result.suspensions_by_user().get(&1).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_json_report() {
+        let mut points_by_user = HashMap::new();
+        points_by_user.insert(0, points(5));
+        points_by_user.insert(1, points(7));
+        let result = GameResult::new(3, points_by_user, vec![rustic_round(1, 0, vec![]), rustic_round(2, 1, vec![0])],
+                                     vec![1], None, vec![]);
+        let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(json["winners"][0], 1);
+        assert!(json["decided_by"].is_null());
+        assert_eq!(json["round_count"], 2);
+        assert_eq!(json["standings"][0]["player"], 1);
+        assert_eq!(json["standings"][0]["points"], 7.0);
+        assert_eq!(json["standings"][1]["suspensions"], 1);
+        assert_eq!(json["rounds"][1]["round_type"], "rustic");
+        assert_eq!(json["rounds"][1]["suspended_players"][0], 0);
+        assert_eq!(json["rounds"][1]["cards"][0]["card"]["suit"], "Hearts");
+        assert_eq!(json["rounds"][1]["cards"][0]["player_signature"], 1);
+        assert_eq!(json["rounds"][1]["hand_outcome"]["slowest_player"], 1);
+        assert_eq!(json["eliminated_players"], serde_json::json!([]));
+        assert_eq!(json["standings"][0]["eliminated"], false);
+    }
+
+    #[test]
+    fn test_eliminated_players_go_last() {
+        let mut points_by_user = HashMap::new();
+        points_by_user.insert(0, points(30));
+        points_by_user.insert(1, points(7));
+        points_by_user.insert(2, points(5));
+        let result = GameResult::new(3, points_by_user, vec![], vec![1], None, vec![0]);
+        assert_eq!(result.ranking, vec![1, 2, 0]);
+        let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(json["eliminated_players"][0], 0);
+        assert_eq!(json["standings"][2]["player"], 0);
+        assert_eq!(json["standings"][2]["eliminated"], true);
+    }
+}
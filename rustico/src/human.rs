@@ -0,0 +1,156 @@
+use std::io::{BufRead, BufReader, Stdin, Stdout, Write};
+use std::time::Instant;
+
+use crate::cards::{Card, CardSuit};
+use crate::game_result::RoundType;
+use crate::strategy::{PlayerStrategy, TableView};
+
+fn card_label(card: &Card) -> String {
+    return match card.suit {
+        CardSuit::Joker => "Comodin".to_string(),
+        suit => format!("{} de {}", card.number, suit.name())
+    };
+}
+
+/* Asiento manejado por una persona desde la terminal. En las rondas normales elige la carta por su
+numero en la mano; en las rusticas tira la primera carta apenas aprieta Enter, asi que llega al
+coordinador tan rapido como reaccione. */
+pub struct HumanStrategy<R: BufRead, W: Write> {
+    input: R,
+    output: W
+}
+
+impl HumanStrategy<BufReader<Stdin>, Stdout> {
+    pub fn terminal() -> Self {
+        return HumanStrategy::new(BufReader::new(std::io::stdin()), std::io::stdout());
+    }
+}
+
+impl<R: BufRead, W: Write> HumanStrategy<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        return HumanStrategy{input, output};
+    }
+
+    /* Devuelve None si se termino la entrada. */
+    fn read_line(&mut self) -> Option<String> {
+        let mut line = String::new();
+        return match self.input.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim().to_string())
+        };
+    }
+
+    fn show_table(&mut self, hand: &[Card], table: &TableView) -> std::io::Result<()> {
+        let round_type = match table.round_type {
+            RoundType::Normal => "normal",
+            RoundType::Rustic => "rustica"
+        };
+        writeln!(self.output, "\nRonda {} ({})", table.round, round_type)?;
+        let mut scores: Vec<_> = table.points_by_user.iter().collect();
+        scores.sort_by_key(|(player, _)| **player);
+        for (player, points) in scores {
+            writeln!(self.output, "  Jugador {}: {} puntos", player, points)?;
+        }
+        if !table.suspended_players.is_empty() {
+            writeln!(self.output, "  Suspendidos: {:?}", table.suspended_players)?;
+        }
+        for signed_card in &table.cards_played {
+            writeln!(self.output, "  El jugador {} tiro el {}", signed_card.player_signature,
+                     card_label(&signed_card.card))?;
+        }
+        writeln!(self.output, "Tu mano:")?;
+        for (i, card) in hand.iter().enumerate() {
+            writeln!(self.output, "  {}) {}", i + 1, card_label(card))?;
+        }
+        return Ok(());
+    }
+
+    fn prompt_card(&mut self, hand: &[Card]) -> std::io::Result<usize> {
+        loop {
+            write!(self.output, "Elegi una carta (1-{}): ", hand.len())?;
+            self.output.flush()?;
+            let line = match self.read_line() {
+                Some(line) => line,
+                None => return Ok(0)
+            };
+            match line.parse::<usize>() {
+                Ok(choice) if (1..=hand.len()).contains(&choice) => return Ok(choice - 1),
+                _ => writeln!(self.output, "'{}' no es una carta de la mano.", line)?
+            }
+        }
+    }
+
+    fn race(&mut self, hand: &[Card]) -> std::io::Result<usize> {
+        write!(self.output, "Ronda rustica! Apreta Enter para tirar el {}: ", card_label(&hand[0]))?;
+        self.output.flush()?;
+        let start = Instant::now();
+        self.read_line();
+        writeln!(self.output, "Tiraste en {} ms.", start.elapsed().as_millis())?;
+        return Ok(0);
+    }
+
+    fn play(&mut self, hand: &[Card], table: &TableView) -> std::io::Result<usize> {
+        self.show_table(hand, table)?;
+        return match table.round_type {
+            RoundType::Normal => self.prompt_card(hand),
+            RoundType::Rustic => self.race(hand)
+        };
+    }
+}
+
+impl<R: BufRead, W: Write> PlayerStrategy for HumanStrategy<R, W> {
+    /* Si no se puede usar la terminal se tira la primera carta, para no trabar la partida. */
+    fn choose_card(&mut self, hand: &[Card], table: &TableView) -> usize {
+        return self.play(hand, table).unwrap_or(0);
+    }
+
+    fn description(&self) -> String {
+        return "humano".to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn hand() -> Vec<Card> {
+        return vec![Card{number: 5, suit: CardSuit::Spades}, Card{number: 12, suit: CardSuit::Hearts}];
+    }
+
+    fn play(input: &str, round_type: RoundType) -> (usize, String) {
+        let mut output = Vec::new();
+        let table = TableView{round: 3, round_type, ..TableView::default()};
+        let choice = HumanStrategy::new(Cursor::new(input.as_bytes()), &mut output).choose_card(&hand(), &table);
+        return (choice, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_human_chooses_card_in_normal_round() {
+        let (choice, output) = play("2\n", RoundType::Normal);
+        assert_eq!(choice, 1);
+        assert!(output.contains("Ronda 3 (normal)"));
+        assert!(output.contains("2) 12 de Hearts"));
+    }
+
+    #[test]
+    fn test_invalid_choices_are_asked_again() {
+        let (choice, output) = play("7\nprimera\n1\n", RoundType::Normal);
+        assert_eq!(choice, 0);
+        assert!(output.contains("'7' no es una carta de la mano."));
+        assert!(output.contains("'primera' no es una carta de la mano."));
+    }
+
+    #[test]
+    fn test_closed_input_throws_first_card() {
+        assert_eq!(play("", RoundType::Normal).0, 0);
+    }
+
+    #[test]
+    fn test_rustic_round_waits_for_enter() {
+        let (choice, output) = play("\n", RoundType::Rustic);
+        assert_eq!(choice, 0);
+        assert!(output.contains("Apreta Enter para tirar el 5 de Spades"));
+        assert!(output.contains("Tiraste en"));
+    }
+}
@@ -0,0 +1,25 @@
+pub mod cards;
+pub mod signed_card;
+pub mod player;
+pub mod coordinator;
+pub mod logger;
+pub mod round_scorer;
+pub mod ruleset;
+pub mod config;
+pub mod game;
+pub mod game_result;
+pub mod tiebreak;
+pub mod strategy;
+pub mod human;
+pub mod bot;
+pub mod protocol;
+pub mod remote;
+pub mod transport;
+pub mod sequential;
+pub mod crash;
+pub mod reaction;
+#[cfg(feature = "async")]
+pub mod async_engine;
+
+pub use crate::game::{Game, GameBuilder};
+pub use crate::game_result::{GameResult, RoundResult, RoundType};
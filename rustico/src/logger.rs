@@ -1,37 +1,75 @@
-pub type LogFile = Arc<Mutex<Option<LineWriter<File>>>>;
+pub type LogFile = Arc<Mutex<Option<LogWriter>>>;
 
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::LineWriter;
 use std::sync::{Arc, Mutex};
 use std::error::Error;
+use serde::Deserialize;
 
-pub fn create_logfile(filename: String) -> Result<LogFile, Box<dyn Error>>{
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    #[default]
+    Debug,
+    Info,
+    Error
+}
+
+impl LogLevel {
+    fn label(&self) -> &'static str {
+        return match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Error => "ERROR"
+        };
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        return match level.trim().to_lowercase().as_str() {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(format!("Unknown log level '{}', expected debug, info or error.", level))
+        };
+    }
+}
+
+pub struct LogWriter {
+    writer: LineWriter<File>,
+    level: LogLevel
+}
+
+pub fn create_logfile(filename: String, level: LogLevel) -> Result<LogFile, Box<dyn Error>>{
     let raw_file = File::create(filename)?;
-    let file = LineWriter::new(raw_file);
-    return Ok(Arc::new(Mutex::new(Some(file))));
+    let writer = LineWriter::new(raw_file);
+    return Ok(Arc::new(Mutex::new(Some(LogWriter{writer, level}))));
 }
 
-fn log(file: LogFile, message: String, level: String) -> std::io::Result<()>{
-    if file.lock().unwrap().is_some(){
-        {
-            let mut line_writer_locked = file.lock().unwrap();
-            line_writer_locked.as_mut().unwrap().write_all(format!("{}: {}\n", level, message).as_bytes())?;
-            line_writer_locked.as_mut().unwrap().flush()?;
+fn log(file: LogFile, message: String, level: LogLevel) -> std::io::Result<()>{
+    let mut log_writer_locked = file.lock().unwrap();
+    if let Some(log_writer) = log_writer_locked.as_mut() {
+        if level >= log_writer.level {
+            log_writer.writer.write_all(format!("{}: {}\n", level.label(), message).as_bytes())?;
+            log_writer.writer.flush()?;
         }
     }
     Ok(())
 }
 
 pub fn debug(file: LogFile, message: String) -> std::io::Result<()>{
-    return log(file, message, "DEBUG".to_string());
+    return log(file, message, LogLevel::Debug);
 }
 
 pub fn info(file: LogFile, message: String) -> std::io::Result<()>{
-    return log(file, message, "INFO".to_string());
+    return log(file, message, LogLevel::Info);
 
 }
 
 pub fn error(file: LogFile, message: String) -> std::io::Result<()>{
-    return log(file, message, "ERROR".to_string());
+    return log(file, message, LogLevel::Error);
 }
@@ -1,97 +1,669 @@
 extern crate clap;
 
-mod player;
-mod coordinator;
-mod signed_card;
-mod cards;
-mod logger;
-
-use clap::{App, Arg};
-use std::sync::{mpsc, Barrier, Arc, Mutex, Condvar};
-use std::thread;
-use player::player;
-use coordinator::coordinator;
-use std::io::{Error, ErrorKind};
-use crate::logger::{create_logfile, debug, info, error, LogFile};
-use crate::player::RoundPlayerFlags;
+use clap::{App, Arg, ArgMatches, SubCommand};
 use std::collections::HashMap;
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::process::Child;
+#[cfg(unix)]
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::io::Error;
+use rustico::{Game, GameBuilder};
+use rustico::cards::CardSuit;
+use rustico::strategy::StrategyKind;
+use rustico::human::HumanStrategy;
+use rustico::bot::{BotStrategy, BOT_TIMEOUT_MS};
+use rustico::protocol::Listener;
+use rustico::remote::{join, lobby, REMOTE_TIMEOUT_MS};
+use rustico::reaction::{ReactionTime, Reflexes};
+use rustico::game::Engine;
+use rustico::game_result::GameResult;
+use rustico::sequential::run_batch;
+use rustico::round_scorer::Points;
+use rustico::ruleset::Ruleset;
+use rustico::strategy::PlayerStrategy;
+use std::time::Duration;
+use rustico::logger::{create_logfile, debug, error, LogFile, LogLevel};
+use rustico::config::{load_config, GameConfig};
 
 
-fn check_player_quantity(players: i32) -> bool {
-    return players >= 4 && players % 2 == 0
+fn parse_flag<T: std::str::FromStr>(name: &str, value: &str) -> std::io::Result<T> {
+    return value.trim().parse()
+        .map_err(|_| Error::other(format!("Invalid value '{}' for --{}.", value, name)));
 }
 
-fn set_up_threads(players: i32, log_file: LogFile) -> std::io::Result<()> {
-    let barrier = Arc::new(Barrier::new((players + 1) as usize));
+/* --strategy y --reaction-time reciben un valor para todos los asientos o asiento=valor para uno solo. */
+fn parse_seat_value<T: std::str::FromStr>(name: &str, value: &str) -> std::io::Result<(Option<usize>, T)> {
+    return match value.split_once('=') {
+        Some((seat, seat_value)) => Ok((Some(parse_flag(name, seat)?), parse_flag(name, seat_value)?)),
+        None => Ok((None, parse_flag(name, value)?))
+    };
+}
+
+/* Guarda el valor de un asiento; los asientos anteriores que no tenian uno toman el general. */
+fn set_seat_value<T: Copy>(seat_values: &mut Vec<T>, seat: usize, value: T, default: T) {
+    if seat_values.len() <= seat {
+        seat_values.resize(seat + 1, default);
+    }
+    seat_values[seat] = value;
+}
+
+/* Cada asiento tiene un solo jugador: --human y --bot no pueden repetir un asiento ni tomar uno de los
+remotos, que son los primeros remote_seats. */
+fn check_local_seats(seats: &[(&str, i32)], remote_seats: i32, players: i32) -> std::io::Result<()> {
+    for (i, (flag, seat)) in seats.iter().enumerate() {
+        if !(0..players).contains(seat) {
+            return Err(Error::other(format!("Invalid value '{}' for --{}, expected a seat from 0 to {}.",
+                                            seat, flag, players - 1)));
+        }
+        if *seat < remote_seats {
+            return Err(Error::other(format!("Seat {} is given to --{}, but seats 0 to {} are remote.",
+                                            seat, flag, remote_seats - 1)));
+        }
+        if let Some((other_flag, _)) = seats[..i].iter().find(|(_, other_seat)| other_seat == seat) {
+            return Err(Error::other(format!("Seat {} is given to both --{} and --{}.", seat, other_flag, flag)));
+        }
+    }
+    return Ok(());
+}
+
+fn parse_suit_precedence(value: &str) -> std::io::Result<[CardSuit; 4]> {
+    let suits = value.split(',')
+        .map(|suit| parse_flag::<CardSuit>("suit-precedence", suit))
+        .collect::<std::io::Result<Vec<CardSuit>>>()?;
+    return match suits.as_slice() {
+        [first, second, third, fourth] => Ok([*first, *second, *third, *fourth]),
+        _ => Err(Error::other(format!("Invalid value '{}' for --suit-precedence, expected four suits.", value)))
+    };
+}
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+#[cfg(not(unix))]
+fn unix_sockets_unsupported() -> Error {
+    return Error::other("--unix needs Unix domain sockets, which this system does not have.");
+}
+
+fn print_final_points(ranking: &[i32], points_by_user: &HashMap<i32, Points>, winners: &[i32], seat: i32) {
+    println!("\nPuntos finales:");
+    for player in ranking {
+        let marker = if *player == seat { " (vos)" } else { "" };
+        println!("  Jugador {}{}: {} puntos", player, marker, points_by_user[player]);
+    }
+    println!("Ganadores: {:?}", winners);
+}
 
-    let (tx_card, rx_card) = mpsc::channel();
-    let (tx_deck, rx_deck) = mpsc::channel();
-    let shared_rx_deck = Arc::new(Mutex::new(rx_deck));
+fn print_batch_summary(players: i32, results: &[GameResult]) {
+    println!("Partidas: {}", results.len());
+    for player in 0..players {
+        let wins = results.iter().filter(|result| result.winners.contains(&player)).count();
+        let total: Points = results.iter().map(|result| result.points_by_user[&player]).sum();
+        let average = total / Points::from_integer(results.len() as i64);
+        println!("  Jugador {}: {} victorias ({:.1}%), {:.2} puntos de promedio", player, wins,
+                 100.0 * wins as f64 / results.len() as f64, *average.numer() as f64 / *average.denom() as f64);
+    }
+}
+
+/* Cliente de una partida servida con serve: juega un asiento con una estrategia o desde la terminal. */
+fn join_game(matches: &ArgMatches) -> std::io::Result<()> {
+    let address = matches.value_of("address").unwrap_or(DEFAULT_ADDRESS);
+    let name = matches.value_of("name").unwrap_or("anonimo");
+    let reaction_time = match matches.value_of("reaction-time") {
+        Some(reaction_time) => parse_flag::<ReactionTime>("reaction-time", reaction_time)?,
+        None => ReactionTime::Immediate
+    };
+    let reflexes = Reflexes::new(reaction_time, rand::random());
+    let logfile: LogFile = Arc::new(Mutex::new(None));
+    let strategy: Box<dyn PlayerStrategy + Send> = if matches.is_present("human") {
+        Box::new(HumanStrategy::terminal())
+    } else {
+        let kind = match matches.value_of("strategy") {
+            Some(strategy) => parse_flag::<StrategyKind>("strategy", strategy)?,
+            None => StrategyKind::default()
+        };
+        kind.build(Ruleset::default(), rand::random())
+    };
+    let standings = match matches.value_of("unix") {
+        #[cfg(unix)]
+        Some(path) => join(UnixStream::connect(path)?, name, strategy, reflexes, logfile)?,
+        #[cfg(not(unix))]
+        Some(_) => return Err(unix_sockets_unsupported()),
+        None => join(TcpStream::connect(address)?, name, strategy, reflexes, logfile)?
+    };
+    let mut ranking: Vec<i32> = standings.points_by_user.keys().copied().collect();
+    ranking.sort_by(|a, b| standings.points_by_user[b].cmp(&standings.points_by_user[a]).then(a.cmp(b)));
+    print_final_points(&ranking, &standings.points_by_user, &standings.winners, standings.seat);
+    return Ok(());
+}
 
-    let mut threads = Vec::new();
-    let mut cond_vars_players = HashMap::new();
+/* Espera a los jugadores remotos y les da sus asientos. */
+fn seat_remote_players<L: Listener>(mut builder: GameBuilder, listener: &L, remote_seats: i32, players: i32,
+                                   timeout: Duration, logfile: LogFile) -> std::io::Result<GameBuilder> {
+    for remote in lobby(listener, remote_seats, players, timeout, logfile)? {
+        builder = builder.remote_seat(remote);
+    }
+    return Ok(builder);
+}
 
-    info(log_file.clone(), "Esperando jugadores".to_string())?;
-    for p in 0..players {
-        let tx_clone_player = mpsc::Sender::clone(&tx_card);
-        let barrier_clone = barrier.clone();
-        let shared_rx_deck_clone = shared_rx_deck.clone();
-        let log_file_clone = log_file.clone();
-        let cond_var_pair = Arc::new((Mutex::new(RoundPlayerFlags{is_my_turn: false, can_throw_card: false,
-                                                                    game_ended: false}), Condvar::new()));
-        let cond_var_pair_clone = cond_var_pair.clone();
-        threads.push(thread::spawn(move || {
-            player(log_file_clone, tx_clone_player, barrier_clone,
-                   shared_rx_deck_clone, cond_var_pair, p).unwrap();
-        }));
-        cond_vars_players.insert(p, cond_var_pair_clone);
+/* Lanza un proceso por jugador remoto, cada uno conectado al socket con join. Los deja en processes a
+medida que los lanza, asi se limpian aunque falle alguno. */
+#[cfg(unix)]
+fn spawn_player_processes(path: &str, remote_seats: i32, strategy: StrategyKind, processes: &mut Vec<Child>)
+                          -> std::io::Result<()> {
+    let executable = std::env::current_exe()?;
+    for i in 0..remote_seats {
+        processes.push(Command::new(&executable)
+            .args(["join", "--unix", path, "--name", &format!("proceso-{}", i),
+                   "--strategy", strategy.name()])
+            .stdout(std::process::Stdio::null())
+            .spawn()?);
     }
+    return Ok(());
+}
 
-    info(log_file.clone(), "Iniciando coordinador".to_string())?;
-    threads.push(thread::spawn(move || {
-        coordinator(log_file, players, rx_card, barrier,
-                    tx_deck, cond_vars_players).unwrap();
-    }));
+/* Lo que deja serve fuera de la partida: los procesos lanzados con --spawn-players y el socket Unix.
+Si la partida termina bien se espera a los procesos con finish; si se corta antes, al soltarlo se los
+mata. En los dos casos se borra el socket. */
+#[derive(Default)]
+struct ServeCleanup {
+    player_processes: Vec<Child>,
+    socket_path: Option<String>
+}
 
-    for thread in threads {
-        thread.join().unwrap();
+impl ServeCleanup {
+    fn finish(mut self) -> std::io::Result<()> {
+        for mut process in self.player_processes.drain(..) {
+            process.wait()?;
+        }
+        if let Some(path) = self.socket_path.take() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
     }
+}
 
-    return Ok(())
+impl Drop for ServeCleanup {
+    fn drop(&mut self) {
+        for process in &mut self.player_processes {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+        if let Some(path) = &self.socket_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
 }
 
 fn main() -> std::io::Result<()> {
     let matches = App::new("Rustico simulation")
         .version("1.0")
+        .arg(Arg::with_name("config")
+            .short("c")
+            .long("config")
+            .help("TOML file with the game configuration. Command line flags override its values.")
+            .takes_value(true)
+            .required(false))
         .arg(Arg::with_name("players")
             .short("p")
             .long("players")
             .help("Number of players to participate in the game.")
             .takes_value(true)
-            .required(true))
+            .required(false))
         .arg(Arg::with_name("debug")
             .short("d")
             .long("debug")
             .help("Debug file")
             .takes_value(true)
             .required(false))
+        .arg(Arg::with_name("output-json")
+            .long("output-json")
+            .help("File where the final standings and every round are written as JSON.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("log-level")
+            .long("log-level")
+            .help("Minimum level written to the debug file: debug, info or error.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("seed")
+            .short("s")
+            .long("seed")
+            .help("Seed for the random number generator. Games with the same seed deal the same cards and play the same round types.")
+            .takes_value(true)
+            .validator(|seed| seed.trim().parse::<u64>().map(|_| ())
+                .map_err(|_| format!("'{}' is not a valid seed, expected a non negative integer.", seed)))
+            .required(false))
+        .arg(Arg::with_name("points-faster")
+            .long("points-faster")
+            .help("Points earned by the fastest player of a rustic round.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("points-slower")
+            .long("points-slower")
+            .help("Points earned by the slowest player of a rustic round.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("points-max-card")
+            .long("points-max-card")
+            .help("Points split among the players that threw the max card of a round.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("max-card-split")
+            .long("max-card-split")
+            .help("How max card points are split when the division is not exact: truncate, exact, carry_remainder or remainder_to_earliest.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("suspension-rounds")
+            .long("suspension-rounds")
+            .help("Number of rounds the slowest player of a rustic round is suspended.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("tiebreak")
+            .long("tiebreak")
+            .help("How to decide a tie for the winner: co_winners, most_max_cards, fewest_suspensions or sudden_death.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("rank-order")
+            .long("rank-order")
+            .help("Order of the card numbers: ace_low or ace_high.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("suit-precedence")
+            .long("suit-precedence")
+            .help("Comma separated suits from highest to lowest, used to break max card ties (e.g. spades,hearts,diamonds,clubs).")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("deck")
+            .long("deck")
+            .help("Deck to deal from: french, spanish40 or spanish48.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("decks")
+            .long("decks")
+            .help("Number of decks combined in the shoe. More are added if there are not enough cards for every player.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("jokers")
+            .long("jokers")
+            .help("Number of jokers added to each deck.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("leftover")
+            .long("leftover")
+            .help("What to do with the undealt cards: discard, kitty, draw_pile or tiebreak_deck.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("points-kitty")
+            .long("points-kitty")
+            .help("Points earned for each card thrown of the suit of the kitty card.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("rustic-deadline")
+            .long("rustic-deadline")
+            .help("Milliseconds players have to throw in a rustic round. Without it every card is waited for.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("late-policy")
+            .long("late-policy")
+            .help("What happens to a player that misses the rustic round deadline: slowest or forfeit.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("strategy")
+            .long("strategy")
+            .help("Card strategy for every seat, or seat=strategy for a single seat: dealt_order, random, highest_first, lowest_first or save_high_cards. Can be repeated.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false))
+        .arg(Arg::with_name("reaction-time")
+            .long("reaction-time")
+            .help("Time a player takes to throw in rustic rounds, for every seat or seat=time for a single seat: immediate, fixed:ms, uniform:min,max, normal:mean,std_dev or exponential:mean, in milliseconds. Can be repeated.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false))
+        .arg(Arg::with_name("human")
+            .long("human")
+            .help("Seat played from the terminal: shows the hand, asks for a card in normal rounds and waits for Enter in rustic rounds.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("bot")
+            .long("bot")
+            .help("seat=command: the seat is played by an external program that speaks the protocol in BOT_PROTOCOL.md. Can be repeated.")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false))
+        .arg(Arg::with_name("bot-timeout")
+            .long("bot-timeout")
+            .help("Milliseconds an external bot has to answer each turn.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("engine")
+            .long("engine")
+            .help("Game engine: threads (one thread per player), async (tokio tasks, needs the async feature) or sequential \
+                   (a single thread, reproducible from the seed; --arrival sets who throws first in rustic rounds).")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("arrival")
+            .long("arrival")
+            .help("Arrival order of rustic rounds with the sequential engine: random or seat_order.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("on-crash")
+            .long("on-crash")
+            .help("What to do when a player panics: eliminate its seat, replace it with a fallback strategy, or abort the game.")
+            .takes_value(true)
+            .required(false))
+        .arg(Arg::with_name("games")
+            .long("games")
+            .help("Play this many games with the sequential engine, one per seed starting from --seed, in parallel, and print how each seat did.")
+            .takes_value(true)
+            .conflicts_with_all(&["debug", "output-json"])
+            .required(false))
+        .arg(Arg::with_name("normal-probability")
+            .long("normal-probability")
+            .help("Probability of a round being normal instead of rustic.")
+            .takes_value(true)
+            .required(false))
+        .subcommand(SubCommand::with_name("serve")
+            .about("Waits for remote players on a TCP port and runs the game with them.")
+            .arg(Arg::with_name("listen")
+                .long("listen")
+                .help("Address to listen on.")
+                .takes_value(true)
+                .default_value(DEFAULT_ADDRESS))
+            .arg(Arg::with_name("unix")
+                .long("unix")
+                .help("Path of a Unix domain socket to listen on instead of TCP.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("spawn-players")
+                .long("spawn-players")
+                .help("Start one join process per remote seat on the Unix socket, playing with --strategy.")
+                .requires("unix")
+                .required(false))
+            .arg(Arg::with_name("remote-seats")
+                .long("remote-seats")
+                .help("Number of seats, starting from seat 0, played by remote players. Defaults to every seat.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("remote-timeout")
+                .long("remote-timeout")
+                .help("Milliseconds a remote player has to answer each message, including choosing a card. A player that takes longer is disconnected and its seat crashes.")
+                .takes_value(true)
+                .required(false)))
+        .subcommand(SubCommand::with_name("join")
+            .about("Joins a game started with serve.")
+            .arg(Arg::with_name("address")
+                .long("address")
+                .help("Address of the server.")
+                .takes_value(true)
+                .default_value(DEFAULT_ADDRESS))
+            .arg(Arg::with_name("unix")
+                .long("unix")
+                .help("Path of the Unix domain socket of the server, instead of TCP.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("name")
+                .long("name")
+                .help("Name shown to the server.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("strategy")
+                .long("strategy")
+                .help("Card strategy for the seat: dealt_order, random, highest_first, lowest_first or save_high_cards.")
+                .takes_value(true)
+                .required(false))
+            .arg(Arg::with_name("human")
+                .long("human")
+                .help("Play the seat from the terminal.")
+                .required(false))
+            .arg(Arg::with_name("reaction-time")
+                .long("reaction-time")
+                .help("Time the seat takes to throw in rustic rounds: immediate, fixed:ms, uniform:min,max, normal:mean,std_dev or exponential:mean, in milliseconds. Remote seats do not use the reaction times of the server.")
+                .takes_value(true)
+                .required(false)))
         .get_matches();
 
-    let players: i32 = matches.value_of("players").unwrap().trim().parse().unwrap();
+    if let Some(matches) = matches.subcommand_matches("join") {
+        return join_game(matches);
+    }
+
+    let mut config = match matches.value_of("config") {
+        Some(path) => load_config(path).map_err(Error::other)?,
+        None => GameConfig::default()
+    };
+
+    if let Some(players) = matches.value_of("players") {
+        config.players = Some(parse_flag("players", players)?);
+    }
+    if let Some(seed) = matches.value_of("seed") {
+        config.seed = Some(parse_flag("seed", seed)?);
+    }
+    if let Some(level) = matches.value_of("log-level") {
+        config.log_level = parse_flag::<LogLevel>("log-level", level)?;
+    }
+    if let Some(path) = matches.value_of("debug") {
+        config.output.debug_log = Some(path.to_string());
+    }
+    if let Some(path) = matches.value_of("output-json") {
+        config.output.json = Some(path.to_string());
+    }
+    if let Some(points) = matches.value_of("points-faster") {
+        config.rules.points_faster_player = parse_flag("points-faster", points)?;
+    }
+    if let Some(points) = matches.value_of("points-slower") {
+        config.rules.points_slower_player = parse_flag("points-slower", points)?;
+    }
+    if let Some(points) = matches.value_of("points-max-card") {
+        config.rules.points_max_card = parse_flag("points-max-card", points)?;
+    }
+    if let Some(split) = matches.value_of("max-card-split") {
+        config.rules.max_card_split = parse_flag("max-card-split", split)?;
+    }
+    if let Some(rounds) = matches.value_of("suspension-rounds") {
+        config.rules.suspension_rounds = parse_flag("suspension-rounds", rounds)?;
+    }
+    if let Some(policy) = matches.value_of("tiebreak") {
+        config.rules.tiebreak = parse_flag("tiebreak", policy)?;
+    }
+    if let Some(order) = matches.value_of("rank-order") {
+        config.rules.rank_order = parse_flag("rank-order", order)?;
+    }
+    if let Some(precedence) = matches.value_of("suit-precedence") {
+        config.rules.suit_precedence = Some(parse_suit_precedence(precedence)?);
+    }
+    if let Some(kind) = matches.value_of("deck") {
+        config.rules.deck.kind = parse_flag("deck", kind)?;
+    }
+    if let Some(decks) = matches.value_of("decks") {
+        config.rules.deck.decks = parse_flag("decks", decks)?;
+    }
+    if let Some(jokers) = matches.value_of("jokers") {
+        config.rules.deck.jokers = parse_flag("jokers", jokers)?;
+    }
+    if let Some(policy) = matches.value_of("leftover") {
+        config.rules.leftover = parse_flag("leftover", policy)?;
+    }
+    if let Some(points) = matches.value_of("points-kitty") {
+        config.rules.points_kitty_suit = parse_flag("points-kitty", points)?;
+    }
+    if let Some(deadline) = matches.value_of("rustic-deadline") {
+        config.rules.rustic_deadline_ms = Some(parse_flag("rustic-deadline", deadline)?);
+    }
+    if let Some(policy) = matches.value_of("late-policy") {
+        config.rules.late_policy = parse_flag("late-policy", policy)?;
+    }
+    for value in matches.values_of("strategy").into_iter().flatten() {
+        match parse_seat_value("strategy", value)? {
+            (Some(seat), strategy) => set_seat_value(&mut config.seat_strategies, seat, strategy, config.strategy),
+            (None, strategy) => config.strategy = strategy
+        }
+    }
+    for value in matches.values_of("reaction-time").into_iter().flatten() {
+        match parse_seat_value("reaction-time", value)? {
+            (Some(seat), reaction_time) => set_seat_value(&mut config.seat_reaction_times, seat, reaction_time,
+                                                          config.reaction_time),
+            (None, reaction_time) => config.reaction_time = reaction_time
+        }
+    }
+    if let Some(engine) = matches.value_of("engine") {
+        config.engine = parse_flag("engine", engine)?;
+    }
+    if let Some(arrival) = matches.value_of("arrival") {
+        config.arrival = parse_flag("arrival", arrival)?;
+    }
+    if let Some(policy) = matches.value_of("on-crash") {
+        config.on_crash = parse_flag("on-crash", policy)?;
+    }
+    if let Some(probability) = matches.value_of("normal-probability") {
+        config.rules.normal_round_probability = parse_flag("normal-probability", probability)?;
+    }
+
     let mut logfile: LogFile = Arc::new(Mutex::new(None));
-    if matches.is_present("debug") {
-        logfile = create_logfile(matches.value_of("debug").unwrap().to_string()).unwrap();
+    if let Some(path) = &config.output.debug_log {
+        logfile = create_logfile(path.to_string(), config.log_level).unwrap();
         debug(logfile.clone(), "Inicio del logfile".to_string())?;
     }
 
+    let players = match config.validate() {
+        Ok(players) => players,
+        Err(message) => {
+            error(logfile.clone(), format!("ERROR: {}", message))?;
+            return Err(Error::other(message));
+        }
+    };
+    let new_game = || {
+        let mut builder = Game::builder(players)
+            .ruleset(config.rules)
+            .strategy(config.strategy)
+            .engine(config.engine)
+            .arrival(config.arrival)
+            .reaction_time(config.reaction_time)
+            .on_crash(config.on_crash);
+        for (seat, strategy) in config.seat_strategies.iter().enumerate() {
+            builder = builder.seat_strategy(seat as i32, *strategy);
+        }
+        for (seat, reaction_time) in config.seat_reaction_times.iter().enumerate() {
+            builder = builder.seat_reaction_time(seat as i32, *reaction_time);
+        }
+        return builder;
+    };
+    if let Some(games) = matches.value_of("games") {
+        if matches.is_present("human") || matches.is_present("bot") || matches.subcommand_name().is_some() {
+            return Err(Error::other("--games only plays seats with built-in strategies."));
+        }
+        /* El archivo de configuracion tambien puede pedirlos; la tanda solo imprime el resumen. */
+        if config.output.debug_log.is_some() || config.output.json.is_some() {
+            return Err(Error::other("--games does not write the debug log or the JSON result, remove them from [output]."));
+        }
+        let first_seed = config.seed.unwrap_or_else(rand::random);
+        let games = parse_flag::<u64>("games", games)?;
+        if games == 0 {
+            return Err(Error::other("Invalid value '0' for --games."));
+        }
+        let seeds: Vec<u64> = (0..games).map(|i| first_seed.wrapping_add(i)).collect();
+        let results = run_batch(&seeds, |_| new_game()).map_err(Error::other)?;
+        print_batch_summary(players, &results);
+        return Ok(());
+    }
 
-    if !check_player_quantity(players){
-        error(logfile.clone(), "ERROR: Number of players should be greater or equal than four and divisible by two.".to_string())?;
-        return Err(Error::new(ErrorKind::Other,
-                            "Number of players should be greater or equal than four and divisible by two."));
+    let mut builder = new_game().logfile(logfile.clone());
+    if let Some(seed) = config.seed {
+        builder = builder.seed(seed);
     }
+    let human_seat = match matches.value_of("human") {
+        Some(seat) => Some(parse_flag::<i32>("human", seat)?),
+        None => None
+    };
+    let mut bot_seats = Vec::new();
+    for value in matches.values_of("bot").into_iter().flatten() {
+        let (seat, command) = value.split_once('=')
+            .ok_or_else(|| Error::other(format!("Invalid value '{}' for --bot, expected seat=command.", value)))?;
+        bot_seats.push((parse_flag::<i32>("bot", seat)?, command));
+    }
+    let remote_seats = match matches.subcommand_matches("serve") {
+        Some(serve) => {
+            let remote_seats = match serve.value_of("remote-seats") {
+                Some(seats) => parse_flag("remote-seats", seats)?,
+                None => players
+            };
+            if remote_seats < 1 || remote_seats > players {
+                return Err(Error::other(format!("Invalid value '{}' for --remote-seats, expected 1 to {}.",
+                                                remote_seats, players)));
+            }
+            remote_seats
+        },
+        None => 0
+    };
+    /* Se revisa antes de lanzar los bots para no dejar procesos que ningun asiento va a usar. */
+    let local_seats: Vec<(&str, i32)> = human_seat.map(|seat| ("human", seat)).into_iter()
+        .chain(bot_seats.iter().map(|(seat, _)| ("bot", *seat)))
+        .collect();
+    check_local_seats(&local_seats, remote_seats, players)?;
 
-    set_up_threads(players, logfile)?;
+    if let Some(seat) = human_seat {
+        builder = builder.custom_strategy(seat, Box::new(HumanStrategy::terminal()));
+    }
+    let bot_timeout = match matches.value_of("bot-timeout") {
+        Some(timeout) => Duration::from_millis(parse_flag("bot-timeout", timeout)?),
+        None => Duration::from_millis(BOT_TIMEOUT_MS)
+    };
+    for (seat, command) in bot_seats {
+        let bot = BotStrategy::spawn(command, bot_timeout, logfile.clone())?;
+        builder = builder.custom_strategy(seat, Box::new(bot));
+    }
+    let mut cleanup = ServeCleanup::default();
+    if let Some(serve) = matches.subcommand_matches("serve") {
+        let remote_timeout = match serve.value_of("remote-timeout") {
+            Some(timeout) => Duration::from_millis(parse_flag("remote-timeout", timeout)?),
+            None => Duration::from_millis(REMOTE_TIMEOUT_MS)
+        };
+        if remote_timeout.is_zero() {
+            return Err(Error::other("Invalid value '0' for --remote-timeout, expected a positive number of milliseconds."));
+        }
+        /* Se revisa antes de abrir el lobby para no hacer esperar a los jugadores remotos. */
+        if config.engine == Engine::Sequential {
+            return Err(Error::other("Remote seats need the threads or async engine."));
+        }
+        match serve.value_of("unix") {
+            #[cfg(unix)]
+            Some(path) => {
+                let listener = UnixListener::bind(path)?;
+                cleanup.socket_path = Some(path.to_string());
+                if serve.is_present("spawn-players") {
+                    spawn_player_processes(path, remote_seats, config.strategy, &mut cleanup.player_processes)?;
+                } else {
+                    println!("Esperando {} jugadores en {}", remote_seats, path);
+                }
+                builder = seat_remote_players(builder, &listener, remote_seats, players, remote_timeout,
+                                              logfile.clone())?;
+            },
+            #[cfg(not(unix))]
+            Some(_) => return Err(unix_sockets_unsupported()),
+            None => {
+                let listener = TcpListener::bind(serve.value_of("listen").unwrap())?;
+                println!("Esperando {} jugadores en {}", remote_seats, listener.local_addr()?);
+                builder = seat_remote_players(builder, &listener, remote_seats, players, remote_timeout,
+                                              logfile.clone())?;
+            }
+        }
+    }
+    let game = builder.build().map_err(Error::other)?;
+
+    let game_result = game.run()?;
+    cleanup.finish()?;
+
+    if let Some(seat) = human_seat {
+        print_final_points(&game_result.ranking, &game_result.points_by_user, &game_result.winners, seat);
+    }
+
+    if let Some(path) = &config.output.json {
+        game_result.write_json(path)?;
+    }
 
     return Ok(());
 }
@@ -1,56 +1,67 @@
-use std::sync::mpsc::{Sender, Receiver};
-
-use std::sync::{Barrier, Arc, Mutex, Condvar};
-
+use crate::game_result::RoundType;
+use crate::reaction::Reflexes;
 use crate::signed_card::SignedCard;
-use crate::cards::Card;
+use crate::strategy::{PlayerStrategy, TableView};
+use crate::transport::{Notification, PlayerTransport};
 
 use crate::logger::{LogFile, info, debug};
 
-fn receive_deck(rx_deck: Arc<Mutex<Receiver<Vec<Card>>>>) -> Vec<Card> {
-    return rx_deck.lock().unwrap().recv().unwrap();
-}
-
-pub struct RoundPlayerFlags {
-    pub is_my_turn: bool,
-    pub can_throw_card: bool,
-    pub game_ended: bool
-}
-
-pub fn player(log: LogFile, card_sender: Sender<SignedCard>, barrier: Arc<Barrier>,
-              rx_deck: Arc<Mutex<Receiver<Vec<Card>>>>, cond_var: Arc<(Mutex<RoundPlayerFlags>, Condvar)>,
-              player_id: i32) -> std::io::Result<()>{
-
-    let deck = receive_deck(rx_deck);
+/* Juega la partida de un jugador. Lo comparten el motor con hilos y el de tokio, que solo cambian el
+transporte. */
+pub async fn player<T: PlayerTransport>(log: LogFile, mut transport: T, player_id: i32,
+                                        mut strategy: Box<dyn PlayerStrategy + Send>,
+                                        mut reflexes: Reflexes) -> std::io::Result<()>{
 
-    let (lock, cvar) = &*cond_var;
+    let mut hand = transport.receive_hand().await?;
+    strategy.deal(player_id, &hand);
 
-    let mut cards_thrown: usize = 0;
+    let mut cards_thrown: i32 = 0;
 
     loop {
-        barrier.wait();
+        transport.sync_round().await?;
 
-        let mut round_player_flags = lock.lock().unwrap();
+        let turn = match transport.wait_turn().await? {
+            Notification::Turn(turn) => turn,
+            Notification::GameOver(game_over) => {
+                if let Some(last_round) = &game_over.last_round {
+                    strategy.round_result(last_round);
+                }
+                strategy.game_over(&game_over.points_by_user, &game_over.winners);
+                transport.sync_round().await?;
+                info(log.clone(), format!("El jugador {} abandona la partida.", player_id))?;
+                return Ok(());
+            }
+        };
 
-        while !(*round_player_flags).is_my_turn {
-            round_player_flags = cvar.wait(round_player_flags).unwrap();
+        if let Some(last_round) = &turn.last_round {
+            strategy.round_result(last_round);
         }
 
-        if (*round_player_flags).game_ended {
-            barrier.wait();
-            info(log.clone(), format!("El jugador {} abandona la partida.", player_id))?;
-            return Ok(());
+        if let Some(card) = turn.drawn_card {
+            hand.insert(0, card);
+            debug(log.clone(), format!("El jugador {} roba una carta del pozo.", player_id))?;
         }
 
-        if (*round_player_flags).can_throw_card {
-            card_sender.send(SignedCard { card: deck[cards_thrown], player_signature: player_id }).unwrap();
+        if turn.can_throw_card {
+            let table = TableView{cards_thrown, ..turn.table};
+            let chosen = transport.block(|| strategy.choose_card(&hand, &table));
+            if chosen >= hand.len() {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,
+                                               format!("chose card {} from a hand of {}", chosen, hand.len())));
+            }
+            let card = hand.remove(chosen);
+            if table.round_type == RoundType::Rustic {
+                let reaction = reflexes.react();
+                debug(log.clone(), format!("El jugador {} tarda {} ms en reaccionar.", player_id, reaction.as_millis()))?;
+                transport.sleep(reaction).await;
+            }
+            transport.send_card(SignedCard { card, player_signature: player_id })?;
             cards_thrown += 1;
             debug(log.clone(), format!("El jugador {} tiró su carta número {}.", player_id, cards_thrown))?;
         } else {
             debug(log.clone(), format!("El jugador {} se encuentra suspendido, no tira carta en esta ronda.", player_id))?;
         }
 
-        (*round_player_flags).is_my_turn = false;
-        barrier.wait();
+        transport.sync_round().await?;
     }
 }
@@ -0,0 +1,267 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Error, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use serde::{Deserialize, Serialize};
+
+use crate::cards::Card;
+use crate::game_result::{RoundResult, RoundType};
+use crate::round_scorer::{HandOutcome, Points};
+use crate::signed_card::SignedCard;
+use crate::strategy::TableView;
+
+/* Mensajes que intercambian el coordinador y los jugadores remotos, uno por linea en JSON. Son los
+mismos pasos de PlayerTransport: la mano, los avisos de turno y de fin, la carta tirada y la
+sincronizacion de cada ronda (sync del cliente y proceed del coordinador cuando la barrera se libera).
+Las cartas viajan como Card, igual que por los canales de la partida local. Los puntos se mandan como
+pares [jugador, "7/2"], con los puntos como texto para no perder precision. */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    Join { name: String },
+    Welcome { seat: i32, players: i32 },
+    Deal { seat: i32, hand: Vec<Card> },
+    Sync,
+    Proceed,
+    YourTurn { can_throw_card: bool, drawn_card: Option<Card>, table: WireTable, last_round: Option<WireRound> },
+    Throw { card: Card },
+    GameOver { points: Vec<(i32, String)>, winners: Vec<i32>, last_round: Option<WireRound> }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WireTable {
+    pub player_id: i32,
+    pub cards_thrown: i32,
+    pub round: i32,
+    pub round_type: RoundType,
+    pub cards_played: Vec<SignedCard>,
+    pub points: Vec<(i32, String)>,
+    pub suspended_players: Vec<i32>
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WireRound {
+    pub round: i32,
+    pub round_type: RoundType,
+    pub cards: Vec<SignedCard>,
+    pub earned_points: Vec<(i32, String)>,
+    pub max_card_points: String,
+    pub carried_points: String,
+    pub players_with_max_card: Vec<i32>,
+    pub slowest_player: Option<i32>,
+    pub fastest_player: Option<i32>,
+    pub suspended_players: Vec<i32>,
+    #[serde(default)]
+    pub late_players: Vec<i32>
+}
+
+fn invalid_data(message: String) -> Error {
+    return Error::new(ErrorKind::InvalidData, message);
+}
+
+pub fn points_to_wire(points_by_user: &HashMap<i32, Points>) -> Vec<(i32, String)> {
+    let mut points: Vec<(i32, String)> = points_by_user.iter()
+        .map(|(player, points)| (*player, points.to_string()))
+        .collect();
+    points.sort();
+    return points;
+}
+
+fn parse_points(points: &str) -> std::io::Result<Points> {
+    return points.parse().map_err(|_| invalid_data(format!("Invalid points '{}'.", points)));
+}
+
+pub fn points_from_wire(points_by_user: &[(i32, String)]) -> std::io::Result<HashMap<i32, Points>> {
+    return points_by_user.iter().map(|(player, points)| Ok((*player, parse_points(points)?))).collect();
+}
+
+impl WireTable {
+    pub fn new(table: &TableView) -> Self {
+        return WireTable{player_id: table.player_id, cards_thrown: table.cards_thrown, round: table.round,
+                         round_type: table.round_type, cards_played: table.cards_played.clone(),
+                         points: points_to_wire(&table.points_by_user),
+                         suspended_players: table.suspended_players.clone()};
+    }
+
+    pub fn to_table(&self) -> std::io::Result<TableView> {
+        return Ok(TableView{player_id: self.player_id, cards_thrown: self.cards_thrown, round: self.round,
+                            round_type: self.round_type, cards_played: self.cards_played.clone(),
+                            points_by_user: points_from_wire(&self.points)?,
+                            suspended_players: self.suspended_players.clone()});
+    }
+}
+
+impl WireRound {
+    pub fn new(round: &RoundResult) -> Self {
+        let outcome = &round.hand_outcome;
+        let mut players_with_max_card: Vec<i32> = outcome.players_with_max_card.iter().copied().collect();
+        players_with_max_card.sort();
+        return WireRound{round: round.round, round_type: round.round_type, cards: round.cards.clone(),
+                         earned_points: points_to_wire(&outcome.earned_points),
+                         max_card_points: outcome.max_card_points.to_string(),
+                         carried_points: outcome.carried_points.to_string(), players_with_max_card,
+                         slowest_player: outcome.slowest_player, fastest_player: outcome.fastest_player,
+                         suspended_players: round.suspended_players.clone(),
+                         late_players: round.late_players.clone()};
+    }
+
+    pub fn to_round(&self) -> std::io::Result<RoundResult> {
+        let hand_outcome = HandOutcome{earned_points: points_from_wire(&self.earned_points)?,
+                                       max_card_points: parse_points(&self.max_card_points)?,
+                                       carried_points: parse_points(&self.carried_points)?,
+                                       players_with_max_card: self.players_with_max_card.iter().copied()
+                                           .collect::<HashSet<i32>>(),
+                                       slowest_player: self.slowest_player, fastest_player: self.fastest_player};
+        return Ok(RoundResult{round: self.round, round_type: self.round_type, cards: self.cards.clone(),
+                              hand_outcome, suspended_players: self.suspended_players.clone(),
+                              late_players: self.late_players.clone()});
+    }
+}
+
+pub fn send_message<W: Write>(writer: &mut W, message: &Message) -> std::io::Result<()> {
+    let line = serde_json::to_string(message).map_err(Error::other)?;
+    writeln!(writer, "{}", line)?;
+    return writer.flush();
+}
+
+/* Devuelve None cuando el otro extremo cerro la conexion. */
+pub fn read_message<R: BufRead>(reader: &mut R) -> std::io::Result<Option<Message>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    return serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| invalid_data(format!("Invalid message '{}': {}", line.trim(), e)));
+}
+
+/* Conexion con un jugador remoto, por TCP o por un socket Unix cuando cada jugador corre en su propio
+proceso. Se necesita poder duplicarla para leer y escribir por separado. */
+pub trait Connection: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> std::io::Result<Self>;
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()>;
+
+    /* Manda cada mensaje apenas se escribe. Los mensajes son cortos y van y vienen en cada paso de la
+    ronda, asi que no conviene esperar para juntarlos. */
+    fn set_nodelay(&self) -> std::io::Result<()> {
+        return Ok(());
+    }
+}
+
+impl Connection for TcpStream {
+    fn try_clone(&self) -> std::io::Result<Self> {
+        return TcpStream::try_clone(self);
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        return TcpStream::set_read_timeout(self, timeout);
+    }
+
+    fn set_nodelay(&self) -> std::io::Result<()> {
+        return TcpStream::set_nodelay(self, true);
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn try_clone(&self) -> std::io::Result<Self> {
+        return UnixStream::try_clone(self);
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        return UnixStream::set_read_timeout(self, timeout);
+    }
+}
+
+/* Donde el coordinador espera a los jugadores remotos. El lobby lo usa sin bloquear para poder seguir
+atendiendo a los que ya se conectaron. */
+pub trait Listener {
+    type Connection: Connection;
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()>;
+
+    /* Devuelve un error WouldBlock si no hay nadie esperando. La conexion aceptada siempre bloquea. */
+    fn accept_connection(&self) -> std::io::Result<Self::Connection>;
+}
+
+impl Listener for TcpListener {
+    type Connection = TcpStream;
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        return TcpListener::set_nonblocking(self, nonblocking);
+    }
+
+    fn accept_connection(&self) -> std::io::Result<TcpStream> {
+        let (stream, _) = self.accept()?;
+        stream.set_nonblocking(false)?;
+        return Ok(stream);
+    }
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Connection = UnixStream;
+
+    fn set_nonblocking(&self, nonblocking: bool) -> std::io::Result<()> {
+        return UnixListener::set_nonblocking(self, nonblocking);
+    }
+
+    fn accept_connection(&self) -> std::io::Result<UnixStream> {
+        let (stream, _) = self.accept()?;
+        stream.set_nonblocking(false)?;
+        return Ok(stream);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::cards::CardSuit;
+    use crate::round_scorer::points;
+
+    #[test]
+    fn test_messages_are_json_lines() {
+        let mut buffer = Vec::new();
+        let card = Card{number: 3, suit: CardSuit::Hearts};
+        send_message(&mut buffer, &Message::Throw{card}).unwrap();
+        send_message(&mut buffer, &Message::Welcome{seat: 1, players: 4}).unwrap();
+        send_message(&mut buffer, &Message::Sync).unwrap();
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], r#"{"type":"throw","card":{"number":3,"suit":"Hearts"}}"#);
+        assert_eq!(lines[2], r#"{"type":"sync"}"#);
+        let mut reader = Cursor::new(buffer);
+        assert_eq!(read_message(&mut reader).unwrap(), Some(Message::Throw{card}));
+        assert_eq!(read_message(&mut reader).unwrap(), Some(Message::Welcome{seat: 1, players: 4}));
+        assert_eq!(read_message(&mut reader).unwrap(), Some(Message::Sync));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+        assert!(read_message(&mut Cursor::new("{\"type\":\"shout\"}\n")).is_err());
+    }
+
+    #[test]
+    fn test_round_survives_the_wire() {
+        let mut earned_points = HashMap::new();
+        earned_points.insert(0, Points::new(7, 2));
+        earned_points.insert(1, points(-5));
+        let hand_outcome = HandOutcome{earned_points, max_card_points: Points::new(7, 2), carried_points: points(0),
+                                       players_with_max_card: vec![0].into_iter().collect(),
+                                       slowest_player: Some(1), fastest_player: Some(0)};
+        let round = RoundResult{round: 2, round_type: RoundType::Rustic, hand_outcome, suspended_players: vec![2],
+                                late_players: vec![3], cards: vec![SignedCard{card: Card{number: 1, suit: CardSuit::Clubs}, player_signature: 0}]};
+        let message = Message::GameOver{points: vec![], winners: vec![0], last_round: Some(WireRound::new(&round))};
+        let json = serde_json::to_string(&message).unwrap();
+        let received = match serde_json::from_str(&json).unwrap() {
+            Message::GameOver{last_round: Some(round), ..} => round.to_round().unwrap(),
+            _ => panic!("Expected the last round")
+        };
+        assert_eq!(received.hand_outcome.earned_points, round.hand_outcome.earned_points);
+        assert_eq!(received.hand_outcome.players_with_max_card, round.hand_outcome.players_with_max_card);
+        assert_eq!(received.cards, round.cards);
+        assert_eq!(received.suspended_players, vec![2]);
+        assert_eq!(received.late_players, vec![3]);
+    }
+}
@@ -0,0 +1,194 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Exp, Normal};
+use serde::{Deserialize, Serialize};
+
+/* Cuanto tarda un jugador en tirar su carta en una ronda rustica, en milisegundos. Hace que haya
+jugadores rapidos y lentos de verdad en lugar de depender de como el sistema operativo reparte los
+hilos. Se escribe como en --reaction-time: "fixed:200", "uniform:100,300", "normal:250,40" o
+"exponential:200" (media). */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum ReactionTime {
+    /* Tira apenas puede. */
+    #[default]
+    Immediate,
+    Fixed { ms: f64 },
+    Uniform { min_ms: f64, max_ms: f64 },
+    Normal { mean_ms: f64, std_dev_ms: f64 },
+    Exponential { mean_ms: f64 }
+}
+
+impl ReactionTime {
+    pub fn description(&self) -> String {
+        return match self {
+            ReactionTime::Immediate => "inmediata".to_string(),
+            ReactionTime::Fixed{ms} => format!("fija de {} ms", ms),
+            ReactionTime::Uniform{min_ms, max_ms} => format!("uniforme entre {} y {} ms", min_ms, max_ms),
+            ReactionTime::Normal{mean_ms, std_dev_ms} => format!("normal de {} ms con desvio {} ms", mean_ms, std_dev_ms),
+            ReactionTime::Exponential{mean_ms} => format!("exponencial de {} ms de media", mean_ms)
+        };
+    }
+
+    /* Chequea los valores de los tiempos que se arman sin pasar por from_str. */
+    pub fn validate(&self) -> Result<(), String> {
+        let times = match *self {
+            ReactionTime::Immediate => vec![],
+            ReactionTime::Fixed{ms} => vec![ms],
+            ReactionTime::Uniform{min_ms, max_ms} => vec![min_ms, max_ms],
+            ReactionTime::Normal{mean_ms, std_dev_ms} => vec![mean_ms, std_dev_ms],
+            ReactionTime::Exponential{mean_ms} => vec![mean_ms]
+        };
+        if times.iter().any(|ms| !ms.is_finite() || *ms < 0.0) {
+            return Err(format!("Invalid reaction time '{}', times must be non negative milliseconds.", String::from(*self)));
+        }
+        return match *self {
+            ReactionTime::Uniform{min_ms, max_ms} if min_ms > max_ms =>
+                Err(format!("Invalid reaction time '{}', the minimum is greater than the maximum.", String::from(*self))),
+            ReactionTime::Exponential{mean_ms} if mean_ms <= 0.0 =>
+                Err(format!("Invalid reaction time '{}', the mean must be greater than zero.", String::from(*self))),
+            _ => Ok(())
+        };
+    }
+
+    /* Los tiempos negativos de la normal se toman como cero y los que no entran en un Duration como el
+    maximo. */
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Duration {
+        let ms = match *self {
+            ReactionTime::Immediate => 0.0,
+            ReactionTime::Fixed{ms} => ms,
+            ReactionTime::Uniform{min_ms, max_ms} => if min_ms < max_ms { rng.gen_range(min_ms, max_ms) } else { min_ms },
+            ReactionTime::Normal{mean_ms, std_dev_ms} => Normal::new(mean_ms, std_dev_ms).unwrap().sample(rng),
+            ReactionTime::Exponential{mean_ms} => Exp::new(1.0 / mean_ms).unwrap().sample(rng)
+        };
+        return Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).unwrap_or(Duration::MAX);
+    }
+}
+
+fn parse_ms(value: &str, reaction_time: &str) -> Result<f64, String> {
+    return match value.trim().parse::<f64>() {
+        Ok(ms) => Ok(ms),
+        Err(_) => Err(format!("Invalid reaction time '{}', times must be non negative milliseconds.", reaction_time))
+    };
+}
+
+impl std::str::FromStr for ReactionTime {
+    type Err = String;
+
+    fn from_str(reaction_time: &str) -> Result<Self, Self::Err> {
+        let lowercase = reaction_time.trim().to_lowercase();
+        let (kind, values) = lowercase.split_once(':').unwrap_or((lowercase.as_str(), ""));
+        let values = values.split(',')
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| parse_ms(value, reaction_time))
+            .collect::<Result<Vec<f64>, String>>()?;
+        let parsed = match (kind, values.as_slice()) {
+            ("immediate", []) => Ok(ReactionTime::Immediate),
+            ("fixed", [ms]) => Ok(ReactionTime::Fixed{ms: *ms}),
+            ("uniform", [min_ms, max_ms]) => Ok(ReactionTime::Uniform{min_ms: *min_ms, max_ms: *max_ms}),
+            ("normal", [mean_ms, std_dev_ms]) => Ok(ReactionTime::Normal{mean_ms: *mean_ms, std_dev_ms: *std_dev_ms}),
+            ("exponential", [mean_ms]) => Ok(ReactionTime::Exponential{mean_ms: *mean_ms}),
+            _ => Err(format!("Invalid reaction time '{}', expected immediate, fixed:ms, uniform:min,max, \
+                              normal:mean,std_dev or exponential:mean.", reaction_time))
+        }?;
+        parsed.validate()?;
+        return Ok(parsed);
+    }
+}
+
+impl From<ReactionTime> for String {
+    fn from(reaction_time: ReactionTime) -> Self {
+        return match reaction_time {
+            ReactionTime::Immediate => "immediate".to_string(),
+            ReactionTime::Fixed{ms} => format!("fixed:{}", ms),
+            ReactionTime::Uniform{min_ms, max_ms} => format!("uniform:{},{}", min_ms, max_ms),
+            ReactionTime::Normal{mean_ms, std_dev_ms} => format!("normal:{},{}", mean_ms, std_dev_ms),
+            ReactionTime::Exponential{mean_ms} => format!("exponential:{}", mean_ms)
+        };
+    }
+}
+
+impl TryFrom<String> for ReactionTime {
+    type Error = String;
+
+    fn try_from(reaction_time: String) -> Result<Self, Self::Error> {
+        return reaction_time.parse();
+    }
+}
+
+/* Tiempo de reaccion de un asiento, con su propio generador para que la partida se pueda reproducir. */
+pub struct Reflexes {
+    reaction_time: ReactionTime,
+    rng: StdRng
+}
+
+impl Reflexes {
+    pub fn new(reaction_time: ReactionTime, seed: u64) -> Self {
+        return Reflexes{reaction_time, rng: StdRng::seed_from_u64(seed)};
+    }
+
+    pub fn reaction_time(&self) -> ReactionTime {
+        return self.reaction_time;
+    }
+
+    pub fn react(&mut self) -> Duration {
+        return self.reaction_time.sample(&mut self.rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reaction_times() {
+        assert_eq!("fixed:200".parse(), Ok(ReactionTime::Fixed{ms: 200.0}));
+        assert_eq!("Uniform:100, 300".parse(), Ok(ReactionTime::Uniform{min_ms: 100.0, max_ms: 300.0}));
+        assert_eq!("normal:250,40".parse(), Ok(ReactionTime::Normal{mean_ms: 250.0, std_dev_ms: 40.0}));
+        assert_eq!("exponential:200".parse(), Ok(ReactionTime::Exponential{mean_ms: 200.0}));
+        assert_eq!("immediate".parse(), Ok(ReactionTime::Immediate));
+        for invalid in ["fixed", "fixed:-1", "uniform:300,100", "normal:1", "exponential:0", "gaussian:1,2"].iter() {
+            assert!(invalid.parse::<ReactionTime>().is_err(), "{}", invalid);
+        }
+        let normal = ReactionTime::Normal{mean_ms: 250.0, std_dev_ms: 40.5};
+        assert_eq!(String::from(normal).parse(), Ok(normal));
+    }
+
+    #[test]
+    fn test_validate_reaction_times_built_by_hand() {
+        assert!(ReactionTime::Normal{mean_ms: 250.0, std_dev_ms: 40.0}.validate().is_ok());
+        assert!(ReactionTime::Normal{mean_ms: 250.0, std_dev_ms: -1.0}.validate().is_err());
+        assert!(ReactionTime::Fixed{ms: f64::NAN}.validate().is_err());
+        assert!(ReactionTime::Fixed{ms: f64::INFINITY}.validate().is_err());
+        assert!(ReactionTime::Uniform{min_ms: 30.0, max_ms: 10.0}.validate().is_err());
+        assert!(ReactionTime::Exponential{mean_ms: 0.0}.validate().is_err());
+        assert_eq!(ReactionTime::Fixed{ms: 1e300}.sample(&mut StdRng::seed_from_u64(1)), Duration::MAX);
+    }
+
+    #[test]
+    fn test_samples_follow_the_distribution() {
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(ReactionTime::Immediate.sample(&mut rng), Duration::from_millis(0));
+        assert_eq!(ReactionTime::Fixed{ms: 20.0}.sample(&mut rng), Duration::from_millis(20));
+        for _ in 0..100 {
+            let uniform = ReactionTime::Uniform{min_ms: 10.0, max_ms: 30.0}.sample(&mut rng);
+            assert!(uniform >= Duration::from_millis(10) && uniform <= Duration::from_millis(30));
+            assert!(ReactionTime::Normal{mean_ms: 1.0, std_dev_ms: 50.0}.sample(&mut rng) >= Duration::from_millis(0));
+        }
+        let mean: f64 = (0..2000).map(|_| ReactionTime::Exponential{mean_ms: 40.0}.sample(&mut rng).as_secs_f64())
+            .sum::<f64>() / 2000.0;
+        assert!((mean - 0.040).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_reflexes_are_reproducible() {
+        let uniform = ReactionTime::Uniform{min_ms: 0.0, max_ms: 100.0};
+        let mut first = Reflexes::new(uniform, 8);
+        let mut second = Reflexes::new(uniform, 8);
+        for _ in 0..10 {
+            assert_eq!(first.react(), second.react());
+        }
+    }
+}
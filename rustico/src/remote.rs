@@ -0,0 +1,462 @@
+use std::collections::HashMap;
+use std::io::{BufReader, Error, ErrorKind, Read, Write};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::cards::Card;
+use crate::logger::{LogFile, info, error};
+use crate::player::player;
+use crate::protocol::{Connection, Listener, Message, WireRound, WireTable, points_from_wire, points_to_wire,
+                      read_message, send_message};
+use crate::reaction::Reflexes;
+use crate::round_scorer::Points;
+use crate::signed_card::SignedCard;
+use crate::strategy::PlayerStrategy;
+use crate::transport::{block_on, GameOver, Notification, PlayerTransport, Turn};
+
+/* Tiempo que tiene un jugador remoto para contestar cada mensaje, incluido elegir su carta. */
+pub const REMOTE_TIMEOUT_MS :u64 = 60000;
+
+type Reader = BufReader<Box<dyn Read + Send>>;
+type Writer = Box<dyn Write + Send>;
+
+fn split<C: Connection>(connection: C) -> std::io::Result<(Reader, Writer)> {
+    connection.set_nodelay()?;
+    let reader: Box<dyn Read + Send> = Box::new(connection.try_clone()?);
+    return Ok((BufReader::new(reader), Box::new(connection)));
+}
+
+fn unexpected(message: Message, expected: &str) -> Error {
+    return Error::new(ErrorKind::InvalidData, format!("sent {:?} instead of {}", message, expected));
+}
+
+/* Asiento jugado por un cliente conectado. El jugador corre en el proceso del cliente; del lado del
+coordinador relay le pasa los mensajes al transporte del asiento. */
+pub struct RemoteSeat {
+    seat: i32,
+    name: String,
+    reader: Reader,
+    writer: Writer,
+    timeout: Duration
+}
+
+impl RemoteSeat {
+    /* Si el cliente tarda mas de timeout en contestar, se corta la conexion. */
+    pub fn new<C: Connection>(connection: C, seat: i32, name: String, timeout: Duration) -> std::io::Result<Self> {
+        connection.set_read_timeout(Some(timeout))?;
+        let (reader, writer) = split(connection)?;
+        return Ok(RemoteSeat{seat, name, reader, writer, timeout});
+    }
+
+    pub fn seat(&self) -> i32 {
+        return self.seat;
+    }
+
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    fn send(&mut self, message: &Message) -> std::io::Result<()> {
+        return send_message(&mut self.writer, message);
+    }
+
+    fn receive(&mut self) -> std::io::Result<Message> {
+        return match read_message(&mut self.reader) {
+            Ok(Some(message)) => Ok(message),
+            Ok(None) => Err(Error::new(ErrorKind::UnexpectedEof, "disconnected")),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                Err(Error::new(ErrorKind::TimedOut, format!("did not answer in {} ms", self.timeout.as_millis())))
+            },
+            Err(e) => Err(e)
+        };
+    }
+
+    /* Espera a que el cliente llegue a sync_round, llega a la barrera por el y le avisa cuando se
+    libera. */
+    async fn sync_round<T: PlayerTransport>(&mut self, transport: &mut T) -> std::io::Result<()> {
+        match transport.block(|| self.receive())? {
+            Message::Sync => {},
+            message => return Err(unexpected(message, "sync"))
+        }
+        transport.sync_round().await?;
+        return self.send(&Message::Proceed);
+    }
+
+    async fn play<T: PlayerTransport>(&mut self, transport: &mut T) -> std::io::Result<()> {
+        /* La mano que deberia tener el cliente, para no aceptarle cartas que no tiene. */
+        let mut hand = transport.receive_hand().await?;
+        self.send(&Message::Deal{seat: self.seat, hand: hand.clone()})?;
+        loop {
+            self.sync_round(transport).await?;
+            let turn = match transport.wait_turn().await? {
+                Notification::Turn(turn) => turn,
+                Notification::GameOver(game_over) => {
+                    self.send(&Message::GameOver{points: points_to_wire(&game_over.points_by_user),
+                                                 winners: game_over.winners,
+                                                 last_round: game_over.last_round.as_ref().map(WireRound::new)})?;
+                    return self.sync_round(transport).await;
+                }
+            };
+            if let Some(card) = turn.drawn_card {
+                hand.insert(0, card);
+            }
+            self.send(&Message::YourTurn{can_throw_card: turn.can_throw_card, drawn_card: turn.drawn_card,
+                                         table: WireTable::new(&turn.table),
+                                         last_round: turn.last_round.as_ref().map(WireRound::new)})?;
+            if turn.can_throw_card {
+                let card = match transport.block(|| self.receive())? {
+                    Message::Throw{card} => card,
+                    message => return Err(unexpected(message, "throw"))
+                };
+                let chosen = hand.iter().position(|in_hand| *in_hand == card).ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidData, format!("threw {:?}, which is not in its hand", card))
+                })?;
+                transport.send_card(SignedCard{card: hand.remove(chosen), player_signature: self.seat})?;
+            }
+            self.sync_round(transport).await?;
+        }
+    }
+}
+
+/* Juega un asiento remoto del lado del coordinador: da los mismos pasos que player con el transporte
+del asiento, pero las cartas las elige el cliente. Si el cliente se desconecta o rompe el protocolo,
+por ejemplo tirando una carta que no tiene, se corta la conexion y el asiento se cae como cualquier
+otro jugador. */
+pub async fn relay<T: PlayerTransport>(log: LogFile, mut transport: T, mut remote: RemoteSeat) -> std::io::Result<()> {
+    let result = remote.play(&mut transport).await;
+    if let Err(e) = &result {
+        error(log, format!("Se corta la conexion con el jugador remoto {} ({}): {}", remote.seat, remote.name, e))?;
+    }
+    return result;
+}
+
+/* Cada cuanto el lobby se fija si hay conexiones nuevas mientras espera presentaciones. */
+const LOBBY_POLL_MS :u64 = 10;
+
+/* Espera el mensaje join de una conexion recien aceptada. */
+fn handshake<C: Connection>(connection: C, timeout: Duration) -> std::io::Result<RemoteSeat> {
+    let mut remote = RemoteSeat::new(connection, -1, String::new(), timeout)?;
+    remote.name = match remote.receive()? {
+        Message::Join{name} => name,
+        message => return Err(unexpected(message, "join"))
+    };
+    return Ok(remote);
+}
+
+/* Recibe conexiones hasta tener seats jugadores. Cada conexion se presenta con un mensaje join en su
+propio hilo, asi una que no contesta no demora a las demas; los asientos se dan en el orden en que
+terminan de presentarse. Las conexiones que no se presentan antes de timeout, o que se cortan antes
+de recibir su asiento, se descartan. */
+pub fn lobby<L: Listener>(listener: &L, seats: i32, players: i32, timeout: Duration, logfile: LogFile)
+                          -> std::io::Result<Vec<RemoteSeat>> {
+    let mut remote_players: Vec<RemoteSeat> = Vec::new();
+    info(logfile.clone(), format!("Esperando {} jugadores remotos", seats))?;
+    let (presented, presentations) = mpsc::channel();
+    listener.set_nonblocking(true)?;
+    while (remote_players.len() as i32) < seats {
+        match listener.accept_connection() {
+            Ok(connection) => {
+                let presented = presented.clone();
+                thread::spawn(move || {
+                    let _ = presented.send(handshake(connection, timeout));
+                });
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {},
+            Err(e) if matches!(e.kind(), ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset) => {
+                error(logfile.clone(), format!("Se descarta una conexion que se corto al aceptarla: {}", e))?;
+            },
+            Err(e) => return Err(e)
+        }
+        let mut remote = match presentations.recv_timeout(Duration::from_millis(LOBBY_POLL_MS)) {
+            Ok(Ok(remote)) => remote,
+            Ok(Err(e)) => {
+                error(logfile.clone(), format!("Se descarta una conexion que no se presento con join: {}", e))?;
+                continue;
+            },
+            Err(_) => continue
+        };
+        remote.seat = remote_players.len() as i32;
+        if let Err(e) = remote.send(&Message::Welcome{seat: remote.seat, players}) {
+            error(logfile.clone(), format!("Se descarta la conexion de {}, que se corto antes de recibir su asiento: {}",
+                                           remote.name, e))?;
+            continue;
+        }
+        info(logfile.clone(), format!("El jugador remoto {} ocupa el asiento {}", remote.name, remote.seat))?;
+        remote_players.push(remote);
+    }
+    listener.set_nonblocking(false)?;
+    return Ok(remote_players);
+}
+
+/* Transporte del cliente: el jugador corre en este proceso y cada paso viaja como un mensaje al
+coordinador. */
+struct SocketPlayer {
+    reader: Reader,
+    writer: Writer,
+    game_over: Sender<GameOver>
+}
+
+impl SocketPlayer {
+    fn receive(&mut self, expected: &str) -> std::io::Result<Message> {
+        return match read_message(&mut self.reader)? {
+            Some(message) => Ok(message),
+            None => Err(Error::other(format!("The server closed the connection while waiting for {}.", expected)))
+        };
+    }
+}
+
+fn unexpected_from_server(message: Message, expected: &str) -> Error {
+    return Error::new(ErrorKind::InvalidData, format!("The server sent {:?} instead of {}.", message, expected));
+}
+
+impl PlayerTransport for SocketPlayer {
+    async fn receive_hand(&mut self) -> std::io::Result<Vec<Card>> {
+        return match self.receive("deal")? {
+            Message::Deal{hand, ..} => Ok(hand),
+            message => Err(unexpected_from_server(message, "deal"))
+        };
+    }
+
+    async fn wait_turn(&mut self) -> std::io::Result<Notification> {
+        return match self.receive("your_turn")? {
+            Message::YourTurn{can_throw_card, drawn_card, table, last_round} => {
+                let last_round = last_round.map(|round| round.to_round()).transpose()?;
+                Ok(Notification::Turn(Turn{can_throw_card, drawn_card, table: table.to_table()?, last_round}))
+            },
+            Message::GameOver{points, winners, last_round} => {
+                let game_over = GameOver{points_by_user: points_from_wire(&points)?, winners,
+                                         last_round: last_round.map(|round| round.to_round()).transpose()?};
+                let _ = self.game_over.send(game_over.clone());
+                Ok(Notification::GameOver(game_over))
+            },
+            message => Err(unexpected_from_server(message, "your_turn"))
+        };
+    }
+
+    fn send_card(&mut self, signed_card: SignedCard) -> std::io::Result<()> {
+        return send_message(&mut self.writer, &Message::Throw{card: signed_card.card});
+    }
+
+    async fn sync_round(&mut self) -> std::io::Result<()> {
+        send_message(&mut self.writer, &Message::Sync)?;
+        return match self.receive("proceed")? {
+            Message::Proceed => Ok(()),
+            message => Err(unexpected_from_server(message, "proceed"))
+        };
+    }
+
+    async fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/* Como termino la partida para un cliente. */
+#[derive(Debug)]
+pub struct FinalStandings {
+    pub seat: i32,
+    pub points_by_user: HashMap<i32, Points>,
+    pub winners: Vec<i32>
+}
+
+/* Lado del cliente: se presenta en el lobby y juega su asiento con player, con la estrategia y los
+tiempos de reaccion del cliente, hasta el fin de la partida. */
+pub fn join<C: Connection>(connection: C, name: &str, strategy: Box<dyn PlayerStrategy + Send>, reflexes: Reflexes,
+                           logfile: LogFile) -> std::io::Result<FinalStandings> {
+    let (mut reader, mut writer) = split(connection)?;
+    send_message(&mut writer, &Message::Join{name: name.to_string()})?;
+    let seat = match read_message(&mut reader)? {
+        Some(Message::Welcome{seat, ..}) => seat,
+        _ => return Err(Error::other("The server did not welcome the player."))
+    };
+    let (game_over_sender, game_over_receiver) = mpsc::channel();
+    let transport = SocketPlayer{reader, writer, game_over: game_over_sender};
+    block_on(player(logfile, transport, seat, strategy, reflexes))?;
+    let game_over = game_over_receiver.recv()
+        .map_err(|_| Error::other("The game ended without a game over message."))?;
+    return Ok(FinalStandings{seat, points_by_user: game_over.points_by_user, winners: game_over.winners});
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use crate::game::Game;
+    use crate::reaction::ReactionTime;
+    use crate::strategy::StrategyKind;
+    use crate::ruleset::Ruleset;
+
+    fn test_lobby<L: Listener>(listener: &L, seats: i32, players: i32) -> Vec<RemoteSeat> {
+        let timeout = Duration::from_millis(REMOTE_TIMEOUT_MS);
+        return lobby(listener, seats, players, timeout, Arc::new(Mutex::new(None))).unwrap();
+    }
+
+    fn client<C: Connection>(connection: C, name: &str, strategy: StrategyKind) -> FinalStandings {
+        return join(connection, name, strategy.build(Ruleset::default(), 1), Reflexes::new(ReactionTime::Immediate, 1),
+                    Arc::new(Mutex::new(None))).unwrap();
+    }
+
+    #[test]
+    fn test_game_over_tcp_on_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let clients: Vec<_> = (0..2).map(|i| {
+            thread::spawn(move || client(TcpStream::connect(address).unwrap(), &format!("cliente {}", i),
+                                         StrategyKind::HighestFirst))
+        }).collect();
+
+        let remote_players = test_lobby(&listener, 2, 3);
+        let mut builder = Game::builder(3).seed(4).strategy(StrategyKind::HighestFirst);
+        for remote in remote_players {
+            builder = builder.remote_seat(remote);
+        }
+        let result = builder.build().unwrap().run().unwrap();
+
+        let mut seats: Vec<i32> = Vec::new();
+        for client in clients {
+            let standings = client.join().unwrap();
+            assert_eq!(standings.points_by_user, result.points_by_user);
+            assert_eq!(standings.winners, result.winners);
+            seats.push(standings.seat);
+        }
+        seats.sort();
+        assert_eq!(seats, vec![0, 1]);
+
+        /* Los clientes corren el mismo jugador que los asientos locales: sin rondas rusticas la partida
+        es la misma. */
+        let ruleset = Ruleset{normal_round_probability: 1.0, ..Ruleset::default()};
+        let local = Game::builder(3).seed(4).ruleset(ruleset).strategy(StrategyKind::HighestFirst)
+            .build().unwrap().run().unwrap();
+        let remote_client = thread::spawn(move || client(TcpStream::connect(address).unwrap(), "remoto",
+                                                         StrategyKind::HighestFirst));
+        let remote = test_lobby(&listener, 1, 3).pop().unwrap();
+        let result = Game::builder(3).seed(4).ruleset(ruleset).strategy(StrategyKind::HighestFirst)
+            .remote_seat(remote).build().unwrap().run().unwrap();
+        remote_client.join().unwrap();
+        assert_eq!(result.points_by_user, local.points_by_user);
+        for (round, local_round) in result.rounds.iter().zip(&local.rounds) {
+            assert_eq!(round.cards, local_round.cards);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_game_over_unix_socket() {
+        use std::os::unix::net::{UnixListener, UnixStream};
+        let path = std::env::temp_dir().join(format!("rustico-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let client_path = path.clone();
+        let client = thread::spawn(move || client(UnixStream::connect(client_path).unwrap(), "proceso",
+                                                  StrategyKind::LowestFirst));
+        let remote = test_lobby(&listener, 1, 2).pop().unwrap();
+        let result = Game::builder(2).seed(3).remote_seat(remote).build().unwrap().run().unwrap();
+        let standings = client.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(standings.seat, 0);
+        assert_eq!(standings.points_by_user, result.points_by_user);
+    }
+
+    #[test]
+    fn test_silent_connection_does_not_hold_the_lobby() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let silent = TcpStream::connect(address).unwrap();
+        let mut rude = TcpStream::connect(address).unwrap();
+        writeln!(rude, "hola").unwrap();
+        let client = thread::spawn(move || client(TcpStream::connect(address).unwrap(), "puntual",
+                                                  StrategyKind::HighestFirst));
+        let start = std::time::Instant::now();
+        let remote = test_lobby(&listener, 1, 2).pop().unwrap();
+        /* Con la conexion callada ocupando el lobby habria que esperar el tiempo limite entero. */
+        assert!(start.elapsed() < Duration::from_millis(REMOTE_TIMEOUT_MS / 2));
+        assert_eq!((remote.seat(), remote.name()), (0, "puntual"));
+        Game::builder(2).seed(3).remote_seat(remote).build().unwrap().run().unwrap();
+        assert_eq!(client.join().unwrap().seat, 0);
+        drop(silent);
+    }
+
+    /* Cliente escrito a mano que juega solo hasta su primer turno: ahi manda lo que diga on_turn, o se
+    queda callado si no dice nada. Devuelve si el coordinador le corto la conexion. */
+    fn scripted_client<F: Fn(&[Card]) -> Option<Message> + Send + 'static>(address: std::net::SocketAddr, on_turn: F)
+                                                                           -> thread::JoinHandle<bool> {
+        return thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            send_message(&mut stream, &Message::Join{name: "tramposo".to_string()}).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let hand = loop {
+                if let Some(Message::Deal{hand, ..}) = read_message(&mut reader).unwrap() {
+                    break hand;
+                }
+            };
+            send_message(&mut stream, &Message::Sync).unwrap();
+            assert_eq!(read_message(&mut reader).unwrap(), Some(Message::Proceed));
+            match read_message(&mut reader).unwrap() {
+                Some(Message::YourTurn{can_throw_card: true, ..}) => {},
+                message => panic!("Expected the first turn, got {:?}", message)
+            }
+            if let Some(message) = on_turn(&hand) {
+                send_message(&mut stream, &message).unwrap();
+            }
+            return matches!(read_message(&mut reader), Ok(None) | Err(_));
+        });
+    }
+
+    fn play_against(listener: &TcpListener, timeout: Duration) -> crate::game_result::GameResult {
+        let remote = lobby(listener, 1, 3, timeout, Arc::new(Mutex::new(None))).unwrap().pop().unwrap();
+        return Game::builder(3).seed(1).remote_seat(remote).build().unwrap().run().unwrap();
+    }
+
+    #[test]
+    fn test_disconnected_client_is_eliminated() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            send_message(&mut stream, &Message::Join{name: "impaciente".to_string()}).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            while let Some(message) = read_message(&mut reader).unwrap() {
+                if let Message::Deal{..} = message {
+                    return;
+                }
+            }
+        });
+        let result = play_against(&listener, Duration::from_millis(REMOTE_TIMEOUT_MS));
+        client.join().unwrap();
+        assert!(result.rounds.iter().all(|round| round.cards.iter().all(|signed_card| signed_card.player_signature != 0)));
+    }
+
+    #[test]
+    fn test_card_not_in_hand_drops_the_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = scripted_client(listener.local_addr().unwrap(), |hand| {
+            Some(Message::Throw{card: Card{number: 99, suit: hand[0].suit}})
+        });
+        let result = play_against(&listener, Duration::from_millis(REMOTE_TIMEOUT_MS));
+        assert!(client.join().unwrap());
+        assert!(result.rounds.iter().all(|round| round.cards.iter().all(|signed_card| signed_card.player_signature != 0)));
+    }
+
+    #[test]
+    fn test_silent_client_is_dropped_after_the_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = scripted_client(listener.local_addr().unwrap(), |_| None);
+        let result = play_against(&listener, Duration::from_millis(100));
+        assert!(client.join().unwrap());
+        assert!(result.rounds.iter().all(|round| round.cards.iter().all(|signed_card| signed_card.player_signature != 0)));
+    }
+
+    #[test]
+    fn test_seat_with_custom_strategy_cannot_be_remote() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (connection, _) = listener.accept().unwrap();
+        let remote = RemoteSeat::new(connection, 0, "remoto".to_string(), Duration::from_secs(1)).unwrap();
+        let game = Game::builder(3)
+            .custom_strategy(0, StrategyKind::Random.build(Ruleset::default(), 1))
+            .remote_seat(remote)
+            .build();
+        assert!(game.is_err());
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::{HashMap, HashSet};
+use num_rational::Ratio;
+use serde::{Deserialize, Serialize};
+
+use crate::signed_card::SignedCard;
+use crate::cards::Card;
+use crate::coordinator::merge_points_hashmaps;
+use crate::ruleset::Ruleset;
+use crate::game_result::{serialize_points, serialize_sorted_map, serialize_sorted_set};
+
+/* Los puntos se llevan como racionales para poder repartir la carta maxima en partes exactas. */
+pub type Points = Ratio<i64>;
+
+pub fn points(value: i32) -> Points {
+    return Points::from_integer(value as i64);
+}
+
+/* Como se reparten los puntos de la carta maxima entre los jugadores que la tiraron cuando la division
+no es exacta. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaxCardSplit {
+    /* Division entera: el resto se pierde. */
+    #[default]
+    Truncate,
+    /* Cada jugador recibe la fraccion exacta. */
+    Exact,
+    /* El resto queda en un pozo que se suma a la carta maxima de la ronda siguiente. */
+    CarryRemainder,
+    /* El resto se lo lleva el primero de los jugadores con la carta maxima en tirarla. */
+    RemainderToEarliest
+}
+
+impl MaxCardSplit {
+    pub fn description(&self) -> &'static str {
+        return match self {
+            MaxCardSplit::Truncate => "division entera",
+            MaxCardSplit::Exact => "fraccion exacta",
+            MaxCardSplit::CarryRemainder => "resto al pozo de la ronda siguiente",
+            MaxCardSplit::RemainderToEarliest => "resto al primero en tirar"
+        };
+    }
+}
+
+impl std::str::FromStr for MaxCardSplit {
+    type Err = String;
+
+    fn from_str(split: &str) -> Result<Self, Self::Err> {
+        return match split.trim().to_lowercase().replace('-', "_").as_str() {
+            "truncate" => Ok(MaxCardSplit::Truncate),
+            "exact" => Ok(MaxCardSplit::Exact),
+            "carry_remainder" => Ok(MaxCardSplit::CarryRemainder),
+            "remainder_to_earliest" => Ok(MaxCardSplit::RemainderToEarliest),
+            _ => Err(format!("Unknown max card split '{}', expected truncate, exact, carry_remainder \
+                              or remainder_to_earliest.", split))
+        };
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HandOutcome {
+    #[serde(serialize_with = "serialize_sorted_map")]
+    pub earned_points: HashMap<i32, Points>,
+    #[serde(serialize_with = "serialize_points")]
+    pub max_card_points: Points,
+    #[serde(serialize_with = "serialize_points")]
+    pub carried_points: Points,
+    #[serde(serialize_with = "serialize_sorted_set")]
+    pub players_with_max_card: HashSet<i32>,
+    pub slowest_player: Option<i32>,
+    pub fastest_player: Option<i32>
+}
+
+/* Regla de puntuacion de una ronda. Recibe las cartas en el orden en que llegaron al coordinador, el
+pozo acumulado de rondas anteriores y los jugadores de una ronda rustica que tiraron despues del tiempo
+limite y deben cargar con la penalizacion del mas lento (en ese caso puede no haber ninguna carta).
+Devuelve los puntos obtenidos por cada uno de esos jugadores. */
+pub trait RoundScorer {
+    fn score(&self, signed_cards: Vec<SignedCard>, max_card_pot: Points, late_players: &[i32]) -> HandOutcome;
+}
+
+pub struct NormalRoundScorer {
+    pub ruleset: Ruleset
+}
+
+impl RoundScorer for NormalRoundScorer {
+    fn score(&self, signed_cards: Vec<SignedCard>, max_card_pot: Points, _late_players: &[i32]) -> HandOutcome {
+        return calculate_normal_hand_points(signed_cards, max_card_pot, &self.ruleset);
+    }
+}
+
+pub struct RusticRoundScorer {
+    pub ruleset: Ruleset
+}
+
+impl RoundScorer for RusticRoundScorer {
+    fn score(&self, signed_cards: Vec<SignedCard>, max_card_pot: Points, late_players: &[i32]) -> HandOutcome {
+        return calculate_rustic_hand_points(signed_cards, max_card_pot, late_players, &self.ruleset);
+    }
+}
+
+pub struct RoundScorers {
+    pub normal: Box<dyn RoundScorer + Send>,
+    pub rustic: Box<dyn RoundScorer + Send>
+}
+
+impl RoundScorers {
+    pub fn new(ruleset: Ruleset) -> Self {
+        return RoundScorers{normal: Box::new(NormalRoundScorer{ruleset}), rustic: Box::new(RusticRoundScorer{ruleset})};
+    }
+}
+
+impl Default for RoundScorers {
+    fn default() -> Self {
+        return RoundScorers::new(Ruleset::default());
+    }
+}
+
+pub fn empty_hand_outcome(signed_cards: &[SignedCard]) -> HandOutcome {
+    let earned_points = signed_cards.iter().map(|signed_card| (signed_card.player_signature, points(0))).collect();
+    return HandOutcome{earned_points, max_card_points: points(0), carried_points: points(0),
+                       players_with_max_card: HashSet::new(), slowest_player: None, fastest_player: None};
+}
+
+/* Reparte los puntos de la carta maxima (mas el pozo) segun la politica. Devuelve lo que recibe cada
+jugador, un extra para el primero en tirarla y lo que pasa a la ronda siguiente. */
+pub fn split_max_card_points(total: Points, players: i32, split: MaxCardSplit) -> (Points, Points, Points) {
+    let whole_total = total.to_integer();
+    let players = players as i64;
+    return match split {
+        MaxCardSplit::Truncate => (Points::from_integer(whole_total / players), points(0), points(0)),
+        MaxCardSplit::Exact => (total / players, points(0), points(0)),
+        MaxCardSplit::CarryRemainder => (Points::from_integer(whole_total / players), points(0),
+                                         Points::from_integer(whole_total % players)),
+        MaxCardSplit::RemainderToEarliest => (Points::from_integer(whole_total / players),
+                                              Points::from_integer(whole_total % players), points(0))
+    };
+}
+
+pub fn calculate_normal_hand_points(mut signed_cards: Vec<SignedCard>, max_card_pot: Points,
+                                    ruleset: &Ruleset) -> HandOutcome{
+    let mut hand_outcome = empty_hand_outcome(&signed_cards);
+    let arrival_order: Vec<i32> = signed_cards.iter().map(|signed_card| signed_card.player_signature).collect();
+
+    signed_cards.sort_by_key(|signed_card| ruleset.card_strength(&signed_card.card));
+
+    let max_card: Card = signed_cards.last().unwrap().card;
+    let max_strength = ruleset.card_strength(&max_card);
+    let mut i = signed_cards.len() - 1;
+    while ruleset.card_strength(&signed_cards[i].card) == max_strength {
+        hand_outcome.players_with_max_card.insert(signed_cards[i].player_signature);
+        if i == 0{
+            break;
+        }
+        i-=1;
+    }
+
+    let (max_card_points, earliest_bonus, carried_points) =
+        split_max_card_points(points(ruleset.points_max_card) + max_card_pot,
+                              hand_outcome.players_with_max_card.len() as i32, ruleset.max_card_split);
+    hand_outcome.max_card_points = max_card_points;
+    hand_outcome.carried_points = carried_points;
+
+    for p in &hand_outcome.players_with_max_card{
+        hand_outcome.earned_points.insert(*p, hand_outcome.max_card_points);
+    }
+
+    let earliest_player = arrival_order.iter().find(|p| hand_outcome.players_with_max_card.contains(p)).unwrap();
+    hand_outcome.earned_points.insert(*earliest_player, max_card_points + earliest_bonus);
+
+    return hand_outcome;
+}
+
+/* Los que llegan tarde cargan con la penalizacion del mas lento en lugar del ultimo que llego a
+tiempo, y el ultimo de ellos queda como el mas lento de la ronda. */
+pub fn calculate_rustic_hand_points(signed_cards: Vec<SignedCard>, max_card_pot: Points, late_players: &[i32],
+                                    ruleset: &Ruleset) -> HandOutcome {
+    /* Si los demas estan suspendidos (por ejemplo en una partida de dos jugadores) el unico que tira
+    no compite con nadie: no hay mas rapido ni mas lento y la ronda se puntua como una normal. */
+    if signed_cards.len() == 1 && late_players.is_empty() {
+        return calculate_normal_hand_points(signed_cards, max_card_pot, ruleset);
+    }
+
+    let mut hand_outcome = empty_hand_outcome(&signed_cards);
+
+    if signed_cards.len() > 1 {
+        let first_player = signed_cards.first().unwrap();
+        hand_outcome.earned_points.insert(first_player.player_signature, points(ruleset.points_faster_player));
+        hand_outcome.fastest_player = Some(first_player.player_signature);
+    }
+
+    let slowest_players = match signed_cards.last() {
+        Some(last_player) if late_players.is_empty() => vec![last_player.player_signature],
+        _ => late_players.to_vec()
+    };
+    for player in &slowest_players {
+        hand_outcome.earned_points.insert(*player, points(ruleset.points_slower_player));
+    }
+    hand_outcome.slowest_player = slowest_players.last().copied();
+
+    /* Nadie llego a tiempo: la carta maxima queda en el pozo. */
+    if signed_cards.is_empty() {
+        hand_outcome.carried_points = max_card_pot;
+        return hand_outcome;
+    }
+
+    let normal_hand_outcome = calculate_normal_hand_points(signed_cards, max_card_pot, ruleset);
+
+    hand_outcome.earned_points = merge_points_hashmaps(hand_outcome.earned_points, normal_hand_outcome.earned_points);
+    hand_outcome.players_with_max_card = normal_hand_outcome.players_with_max_card;
+    hand_outcome.max_card_points = normal_hand_outcome.max_card_points;
+    hand_outcome.carried_points = normal_hand_outcome.carried_points;
+
+    return hand_outcome;
+}
+
+#[cfg(test)]
+#[allow(clippy::unnecessary_cast, clippy::nonminimal_bool, clippy::vec_init_then_push)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::cards::{CardSuit, RankOrder};
+    use crate::ruleset::{POINTS_FASTER_PLAYER, POINTS_SLOWER_PLAYER, POINTS_MAX_CARD};
+
+    #[test]
+    fn test_calculate_normal_hand_points_one_player() {
+        let mut signed_cards = vec![];
+        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
+                                     player_signature: 0});
+        let hand_outcome = calculate_normal_hand_points(signed_cards, points(0), &Ruleset::default());
+        assert_eq!(hand_outcome.earned_points.len(), 1);
+        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), points(POINTS_MAX_CARD));
+        assert_eq!(hand_outcome.max_card_points, points(10));
+        assert_eq!(hand_outcome.players_with_max_card.len(), 1);
+        assert!(hand_outcome.players_with_max_card.contains(&0));
+        assert!(!hand_outcome.slowest_player.is_some());
+        assert!(!hand_outcome.fastest_player.is_some());
+    }
+
+    #[test]
+    fn test_calculate_normal_hand_points_two_players() {
+        let mut signed_cards = vec![];
+        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
+                                     player_signature: 0});
+        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Spades},
+                                     player_signature: 1});
+        let hand_outcome = calculate_normal_hand_points(signed_cards, points(0), &Ruleset::default());
+        assert_eq!(hand_outcome.earned_points.len(), 2);
+        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), points(0));
+        assert_eq!(*hand_outcome.earned_points.get(&(1 as i32)).unwrap(), points(POINTS_MAX_CARD));
+        assert_eq!(hand_outcome.max_card_points, points(10));
+        assert_eq!(hand_outcome.players_with_max_card.len(), 1);
+        assert!(hand_outcome.players_with_max_card.contains(&1));
+        assert!(!hand_outcome.slowest_player.is_some());
+        assert!(!hand_outcome.fastest_player.is_some());
+    }
+
+    #[test]
+    fn test_calculate_normal_hand_points_two_winners() {
+        let mut signed_cards = vec![];
+        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
+                                     player_signature: 0});
+        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Hearts},
+                                     player_signature: 1});
+        signed_cards.push(SignedCard{card: Card{number: 1, suit:CardSuit::Diamonds},
+                                     player_signature: 2});
+        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Clubs},
+                                     player_signature: 3});
+        let hand_outcome = calculate_normal_hand_points(signed_cards, points(0), &Ruleset::default());
+        assert_eq!(hand_outcome.earned_points.len(), 4);
+        assert_eq!(hand_outcome.max_card_points, points(POINTS_MAX_CARD/2));
+        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), points(0));
+        assert_eq!(*hand_outcome.earned_points.get(&(1 as i32)).unwrap(), points(POINTS_MAX_CARD/2));
+        assert_eq!(*hand_outcome.earned_points.get(&(2 as i32)).unwrap(), points(0));
+        assert_eq!(*hand_outcome.earned_points.get(&(3 as i32)).unwrap(), points(POINTS_MAX_CARD/2));
+        assert_eq!(hand_outcome.players_with_max_card.len(), 2);
+        assert!(hand_outcome.players_with_max_card.contains(&1));
+        assert!(hand_outcome.players_with_max_card.contains(&3));
+        assert!(!hand_outcome.slowest_player.is_some());
+        assert!(!hand_outcome.fastest_player.is_some());
+    }
+
+    #[test]
+    fn test_calculate_rustic_hand_points_two_winners() {
+        let mut signed_cards = vec![];
+        signed_cards.push(SignedCard{card: Card{number: 10, suit:CardSuit::Spades},
+                                     player_signature: 1});
+        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Hearts},
+                                     player_signature: 2});
+        signed_cards.push(SignedCard{card: Card{number: 1, suit:CardSuit::Diamonds},
+                                     player_signature: 0});
+        signed_cards.push(SignedCard{card: Card{number: 11, suit:CardSuit::Clubs},
+                                     player_signature: 3});
+        let hand_outcome = calculate_rustic_hand_points(signed_cards, points(0), &[], &Ruleset::default());
+        assert_eq!(hand_outcome.earned_points.len(), 4);
+        assert_eq!(hand_outcome.max_card_points, points(POINTS_MAX_CARD/2));
+        assert!(hand_outcome.slowest_player.is_some());
+        assert!(hand_outcome.fastest_player.is_some());
+        assert_eq!(hand_outcome.slowest_player.unwrap(), 3);
+        assert_eq!(hand_outcome.fastest_player.unwrap(), 1);
+        assert_eq!(*hand_outcome.earned_points.get(&(0 as i32)).unwrap(), points(0));
+        assert_eq!(*hand_outcome.earned_points.get(&(1 as i32)).unwrap(), points(POINTS_FASTER_PLAYER));
+        assert_eq!(*hand_outcome.earned_points.get(&(2 as i32)).unwrap(), points(POINTS_MAX_CARD/2));
+        assert_eq!(*hand_outcome.earned_points.get(&(3 as i32)).unwrap(), points(POINTS_MAX_CARD/2 + POINTS_SLOWER_PLAYER));
+        assert_eq!(hand_outcome.players_with_max_card.len(), 2);
+        assert!(hand_outcome.players_with_max_card.contains(&2));
+        assert!(hand_outcome.players_with_max_card.contains(&3));
+    }
+
+    #[test]
+    fn test_calculate_rustic_hand_points_with_suspended_player() {
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 3, suit:CardSuit::Spades}, player_signature: 0},
+            SignedCard{card: Card{number: 2, suit:CardSuit::Hearts}, player_signature: 2},
+            SignedCard{card: Card{number: 12, suit:CardSuit::Clubs}, player_signature: 3},
+            SignedCard{card: Card{number: 5, suit:CardSuit::Diamonds}, player_signature: 4},
+        ];
+        let hand_outcome = calculate_rustic_hand_points(signed_cards, points(0), &[], &Ruleset::default());
+        assert_eq!(hand_outcome.earned_points.len(), 4);
+        assert!(!hand_outcome.earned_points.contains_key(&1));
+        assert_eq!(*hand_outcome.earned_points.get(&0).unwrap(), points(POINTS_FASTER_PLAYER));
+        assert_eq!(*hand_outcome.earned_points.get(&2).unwrap(), points(0));
+        assert_eq!(*hand_outcome.earned_points.get(&3).unwrap(), points(POINTS_MAX_CARD));
+        assert_eq!(*hand_outcome.earned_points.get(&4).unwrap(), points(POINTS_SLOWER_PLAYER));
+    }
+
+    #[test]
+    fn test_calculate_rustic_hand_points_single_player_has_no_race() {
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 3, suit:CardSuit::Spades}, player_signature: 1},
+        ];
+        let hand_outcome = calculate_rustic_hand_points(signed_cards, points(0), &[], &Ruleset::default());
+        assert_eq!(*hand_outcome.earned_points.get(&1).unwrap(), points(POINTS_MAX_CARD));
+        assert!(hand_outcome.slowest_player.is_none());
+        assert!(hand_outcome.fastest_player.is_none());
+    }
+
+    #[test]
+    fn test_default_scorers_use_built_in_rules() {
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 4, suit:CardSuit::Spades}, player_signature: 0},
+            SignedCard{card: Card{number: 9, suit:CardSuit::Hearts}, player_signature: 1},
+        ];
+        let scorers = RoundScorers::default();
+        let normal_outcome = scorers.normal.score(signed_cards.clone(), points(0), &[]);
+        assert!(normal_outcome.fastest_player.is_none());
+        assert_eq!(*normal_outcome.earned_points.get(&1).unwrap(), points(POINTS_MAX_CARD));
+
+        let rustic_outcome = scorers.rustic.score(signed_cards, points(0), &[]);
+        assert_eq!(rustic_outcome.fastest_player, Some(0));
+        assert_eq!(rustic_outcome.slowest_player, Some(1));
+        assert_eq!(*rustic_outcome.earned_points.get(&1).unwrap(), points(POINTS_MAX_CARD + POINTS_SLOWER_PLAYER));
+    }
+
+    #[test]
+    fn test_scorers_use_ruleset_values() {
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 4, suit:CardSuit::Spades}, player_signature: 0},
+            SignedCard{card: Card{number: 9, suit:CardSuit::Hearts}, player_signature: 1},
+            SignedCard{card: Card{number: 9, suit:CardSuit::Clubs}, player_signature: 2},
+        ];
+        let ruleset = Ruleset{points_faster_player: 3, points_slower_player: -2, points_max_card: 20,
+                              ..Ruleset::default()};
+        let scorers = RoundScorers::new(ruleset);
+        let hand_outcome = scorers.rustic.score(signed_cards, points(0), &[]);
+        assert_eq!(hand_outcome.max_card_points, points(10));
+        assert_eq!(*hand_outcome.earned_points.get(&0).unwrap(), points(3));
+        assert_eq!(*hand_outcome.earned_points.get(&1).unwrap(), points(10));
+        assert_eq!(*hand_outcome.earned_points.get(&2).unwrap(), points(8));
+    }
+
+    fn three_way_tie() -> Vec<SignedCard> {
+        return vec![
+            SignedCard{card: Card{number: 12, suit:CardSuit::Spades}, player_signature: 2},
+            SignedCard{card: Card{number: 3, suit:CardSuit::Hearts}, player_signature: 0},
+            SignedCard{card: Card{number: 12, suit:CardSuit::Clubs}, player_signature: 1},
+            SignedCard{card: Card{number: 12, suit:CardSuit::Hearts}, player_signature: 3},
+        ];
+    }
+
+    #[test]
+    fn test_truncate_split_loses_remainder() {
+        let hand_outcome = calculate_normal_hand_points(three_way_tie(), points(0), &Ruleset::default());
+        assert_eq!(hand_outcome.max_card_points, points(3));
+        assert_eq!(hand_outcome.carried_points, points(0));
+        let total: Points = hand_outcome.earned_points.values().sum();
+        assert_eq!(total, points(9));
+    }
+
+    #[test]
+    fn test_exact_split() {
+        let ruleset = Ruleset{max_card_split: MaxCardSplit::Exact, ..Ruleset::default()};
+        let hand_outcome = calculate_normal_hand_points(three_way_tie(), points(0), &ruleset);
+        assert_eq!(hand_outcome.max_card_points, Points::new(10, 3));
+        assert_eq!(*hand_outcome.earned_points.get(&1).unwrap(), Points::new(10, 3));
+        let total: Points = hand_outcome.earned_points.values().sum();
+        assert_eq!(total, points(POINTS_MAX_CARD));
+    }
+
+    #[test]
+    fn test_carry_remainder_split_uses_and_fills_pot() {
+        let ruleset = Ruleset{max_card_split: MaxCardSplit::CarryRemainder, ..Ruleset::default()};
+        let hand_outcome = calculate_normal_hand_points(three_way_tie(), points(0), &ruleset);
+        assert_eq!(hand_outcome.max_card_points, points(3));
+        assert_eq!(hand_outcome.carried_points, points(1));
+
+        let hand_outcome = calculate_normal_hand_points(three_way_tie(), points(2), &ruleset);
+        assert_eq!(hand_outcome.max_card_points, points(4));
+        assert_eq!(hand_outcome.carried_points, points(0));
+    }
+
+    #[test]
+    fn test_remainder_to_earliest_split() {
+        let ruleset = Ruleset{max_card_split: MaxCardSplit::RemainderToEarliest, ..Ruleset::default()};
+        let hand_outcome = calculate_normal_hand_points(three_way_tie(), points(0), &ruleset);
+        assert_eq!(*hand_outcome.earned_points.get(&2).unwrap(), points(4));
+        assert_eq!(*hand_outcome.earned_points.get(&1).unwrap(), points(3));
+        assert_eq!(*hand_outcome.earned_points.get(&3).unwrap(), points(3));
+        assert_eq!(*hand_outcome.earned_points.get(&0).unwrap(), points(0));
+    }
+
+    #[test]
+    fn test_large_tie_still_earns_points() {
+        let signed_cards: Vec<SignedCard> = (0..12)
+            .map(|p| SignedCard{card: Card{number: 13, suit:CardSuit::Spades}, player_signature: p})
+            .collect();
+        let ruleset = Ruleset{max_card_split: MaxCardSplit::RemainderToEarliest, ..Ruleset::default()};
+        let hand_outcome = calculate_normal_hand_points(signed_cards, points(0), &ruleset);
+        assert_eq!(*hand_outcome.earned_points.get(&0).unwrap(), points(POINTS_MAX_CARD));
+        let total: Points = hand_outcome.earned_points.values().sum();
+        assert_eq!(total, points(POINTS_MAX_CARD));
+    }
+
+    #[test]
+    fn test_ace_high_wins_the_max_card() {
+        let signed_cards = vec![
+            SignedCard{card: Card{number: 13, suit:CardSuit::Spades}, player_signature: 0},
+            SignedCard{card: Card{number: 1, suit:CardSuit::Hearts}, player_signature: 1},
+        ];
+        let ruleset = Ruleset{rank_order: RankOrder::AceHigh, ..Ruleset::default()};
+        let hand_outcome = calculate_normal_hand_points(signed_cards.clone(), points(0), &ruleset);
+        assert_eq!(hand_outcome.players_with_max_card, vec![1].into_iter().collect());
+        let hand_outcome = calculate_normal_hand_points(signed_cards, points(0), &Ruleset::default());
+        assert_eq!(hand_outcome.players_with_max_card, vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_suit_precedence_breaks_max_card_tie() {
+        let ruleset = Ruleset{suit_precedence: Some([CardSuit::Hearts, CardSuit::Clubs,
+                                                     CardSuit::Spades, CardSuit::Diamonds]),
+                              ..Ruleset::default()};
+        let hand_outcome = calculate_normal_hand_points(three_way_tie(), points(0), &ruleset);
+        assert_eq!(hand_outcome.players_with_max_card, vec![3].into_iter().collect());
+        assert_eq!(*hand_outcome.earned_points.get(&3).unwrap(), points(POINTS_MAX_CARD));
+        assert_eq!(*hand_outcome.earned_points.get(&1).unwrap(), points(0));
+    }
+}
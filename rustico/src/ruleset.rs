@@ -0,0 +1,192 @@
+use serde::Deserialize;
+
+use crate::cards::{Card, CardSuit, DeckBuilder, RankOrder};
+use crate::logger::{LogFile, info};
+use crate::tiebreak::TiebreakPolicy;
+use crate::round_scorer::MaxCardSplit;
+use crate::coordinator::{LatePolicy, LeftoverPolicy};
+
+pub const POINTS_FASTER_PLAYER :i32 = 1;
+pub const POINTS_SLOWER_PLAYER :i32 = -5;
+pub const POINTS_MAX_CARD :i32 = 10;
+pub const SUSPENSION_ROUNDS :i32 = 1;
+pub const NORMAL_ROUND_PROBABILITY :f64 = 0.5;
+pub const POINTS_KITTY_SUIT :i32 = 1;
+
+/* Valores de puntaje y penalizaciones con los que se juega una partida. */
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Ruleset {
+    pub points_faster_player: i32,
+    pub points_slower_player: i32,
+    pub points_max_card: i32,
+    pub max_card_split: MaxCardSplit,
+    pub suspension_rounds: i32,
+    pub normal_round_probability: f64,
+    pub tiebreak: TiebreakPolicy,
+    pub rank_order: RankOrder,
+    /* Palos de mayor a menor. Si esta presente desempata las cartas del mismo numero. */
+    pub suit_precedence: Option<[CardSuit; 4]>,
+    pub deck: DeckBuilder,
+    pub leftover: LeftoverPolicy,
+    pub points_kitty_suit: i32,
+    /* Milisegundos que tienen los jugadores para tirar en una ronda rustica desde que arranca. Sin
+    valor se los espera sin limite. */
+    pub rustic_deadline_ms: Option<u64>,
+    pub late_policy: LatePolicy
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        return Ruleset{points_faster_player: POINTS_FASTER_PLAYER, points_slower_player: POINTS_SLOWER_PLAYER,
+                       points_max_card: POINTS_MAX_CARD, max_card_split: MaxCardSplit::default(),
+                       suspension_rounds: SUSPENSION_ROUNDS,
+                       normal_round_probability: NORMAL_ROUND_PROBABILITY, tiebreak: TiebreakPolicy::default(),
+                       rank_order: RankOrder::default(), suit_precedence: None,
+                       deck: DeckBuilder::default(), leftover: LeftoverPolicy::default(),
+                       points_kitty_suit: POINTS_KITTY_SUIT, rustic_deadline_ms: None,
+                       late_policy: LatePolicy::default()};
+    }
+}
+
+impl Ruleset {
+    pub fn validate(&self, players: i32) -> Result<(), String> {
+        if self.suspension_rounds < 0 {
+            return Err("Suspension rounds can not be negative.".to_string());
+        }
+        /* Cada ronda rustica suspende a un solo jugador, por lo que a lo sumo hay suspension_rounds
+        jugadores suspendidos a la vez y tiene que quedar al menos uno que tire carta. */
+        if self.suspension_rounds >= players {
+            return Err("Suspension rounds should be lower than the number of players.".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.normal_round_probability) {
+            return Err("Normal round probability should be between 0 and 1.".to_string());
+        }
+        if self.rustic_deadline_ms == Some(0) {
+            return Err("The rustic round deadline should be greater than zero.".to_string());
+        }
+        self.deck.validate()?;
+        if self.leftover == LeftoverPolicy::TiebreakDeck && self.tiebreak != TiebreakPolicy::SuddenDeath {
+            return Err("The tiebreak_deck leftover policy requires the sudden_death tiebreak.".to_string());
+        }
+        if let Some(precedence) = &self.suit_precedence {
+            for (i, suit) in precedence.iter().enumerate() {
+                if precedence[..i].contains(suit) || !self.deck.kind.suits().contains(suit) {
+                    return Err("Suit precedence should list each suit of the deck exactly once.".to_string());
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    /* Fuerza de una carta para decidir la carta maxima: primero el numero segun el orden elegido y
+    despues, si hay precedencia de palos, la posicion del palo (el primero es el mas alto). */
+    pub fn card_strength(&self, card: &Card) -> (i32, i32) {
+        let suit_strength = match &self.suit_precedence {
+            Some(precedence) => {
+                let position = precedence.iter().position(|suit| *suit == card.suit).unwrap_or(precedence.len());
+                (precedence.len() - position) as i32
+            },
+            None => 0
+        };
+        return (self.rank_order.rank(card), suit_strength);
+    }
+
+    pub fn register(&self, logfile: LogFile) -> std::io::Result<()> {
+        info(logfile.clone(), format!("Puntos para el jugador mas rapido: {}", self.points_faster_player))?;
+        info(logfile.clone(), format!("Puntos para el jugador mas lento: {}", self.points_slower_player))?;
+        info(logfile.clone(), format!("Puntos por la carta maxima: {}", self.points_max_card))?;
+        info(logfile.clone(), format!("Reparto de la carta maxima: {}", self.max_card_split.description()))?;
+        info(logfile.clone(), format!("Rondas de suspension del jugador mas lento: {}", self.suspension_rounds))?;
+        info(logfile.clone(), format!("Probabilidad de ronda normal: {}", self.normal_round_probability))?;
+        info(logfile.clone(), format!("Criterio de desempate: {}", self.tiebreak.description()))?;
+        info(logfile.clone(), format!("Mazo: {} x{} con {} comodines por mazo", self.deck.kind.description(),
+                                      self.deck.decks, self.deck.jokers))?;
+        info(logfile.clone(), format!("Cartas sobrantes: {}", self.leftover.description()))?;
+        if self.leftover == LeftoverPolicy::Kitty {
+            info(logfile.clone(), format!("Puntos por carta del palo bonificado: {}", self.points_kitty_suit))?;
+        }
+        if let Some(deadline) = self.rustic_deadline_ms {
+            info(logfile.clone(), format!("Tiempo límite de las rondas rústicas: {} ms, quien no llega {}", deadline,
+                                          self.late_policy.description()))?;
+        }
+        info(logfile.clone(), format!("Orden de las cartas: {}", self.rank_order.description()))?;
+        match &self.suit_precedence {
+            Some(precedence) => info(logfile, format!("Precedencia de palos: {:?}", precedence))?,
+            None => info(logfile, "Precedencia de palos: ninguna".to_string())?
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::DeckKind;
+
+    #[test]
+    fn test_default_ruleset_is_valid() {
+        assert!(Ruleset::default().validate(4).is_ok());
+    }
+
+    #[test]
+    fn test_suspension_rounds_must_leave_a_player() {
+        let ruleset = Ruleset{suspension_rounds: 4, ..Ruleset::default()};
+        assert!(ruleset.validate(4).is_err());
+        assert!(ruleset.validate(5).is_ok());
+    }
+
+    #[test]
+    fn test_negative_suspension_is_rejected() {
+        assert!(Ruleset{suspension_rounds: -1, ..Ruleset::default()}.validate(4).is_err());
+    }
+
+    #[test]
+    fn test_normal_round_probability_out_of_range_is_rejected() {
+        assert!(Ruleset{normal_round_probability: 1.5, ..Ruleset::default()}.validate(4).is_err());
+        assert!(Ruleset{normal_round_probability: 1.0, ..Ruleset::default()}.validate(4).is_ok());
+    }
+
+    #[test]
+    fn test_rustic_deadline_must_be_positive() {
+        assert!(Ruleset{rustic_deadline_ms: Some(0), ..Ruleset::default()}.validate(4).is_err());
+        assert!(Ruleset{rustic_deadline_ms: Some(1), ..Ruleset::default()}.validate(4).is_ok());
+    }
+
+    #[test]
+    fn test_repeated_suit_precedence_is_rejected() {
+        let precedence = [CardSuit::Spades, CardSuit::Hearts, CardSuit::Spades, CardSuit::Clubs];
+        assert!(Ruleset{suit_precedence: Some(precedence), ..Ruleset::default()}.validate(4).is_err());
+    }
+
+    #[test]
+    fn test_suit_precedence_must_match_the_deck() {
+        let precedence = [CardSuit::Spades, CardSuit::Hearts, CardSuit::Diamonds, CardSuit::Clubs];
+        let ruleset = Ruleset{suit_precedence: Some(precedence), deck: DeckBuilder::new(DeckKind::Spanish40),
+                              ..Ruleset::default()};
+        assert!(ruleset.validate(4).is_err());
+        let ruleset = Ruleset{suit_precedence: Some(DeckKind::Spanish40.suits()), ..ruleset};
+        assert!(ruleset.validate(4).is_ok());
+        assert!(Ruleset{deck: DeckBuilder::default().decks(0), ..Ruleset::default()}.validate(4).is_err());
+    }
+
+    #[test]
+    fn test_tiebreak_deck_requires_sudden_death() {
+        let ruleset = Ruleset{leftover: LeftoverPolicy::TiebreakDeck, ..Ruleset::default()};
+        assert!(ruleset.validate(5).is_err());
+        assert!(Ruleset{tiebreak: TiebreakPolicy::SuddenDeath, ..ruleset}.validate(5).is_ok());
+    }
+
+    #[test]
+    fn test_card_strength() {
+        let ace = Card{number: 1, suit: CardSuit::Clubs};
+        let king = Card{number: 13, suit: CardSuit::Spades};
+        let ruleset = Ruleset{rank_order: RankOrder::AceHigh,
+                              suit_precedence: Some([CardSuit::Spades, CardSuit::Hearts,
+                                                     CardSuit::Diamonds, CardSuit::Clubs]),
+                              ..Ruleset::default()};
+        assert!(ruleset.card_strength(&ace) > ruleset.card_strength(&king));
+        assert!(ruleset.card_strength(&Card{number: 1, suit: CardSuit::Spades}) > ruleset.card_strength(&ace));
+        assert!(Ruleset::default().card_strength(&ace) < Ruleset::default().card_strength(&king));
+    }
+}
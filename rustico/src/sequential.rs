@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::coordinator::{game_over, GameSettings, GameState};
+use crate::crash::panic_message;
+use crate::game::{Engine, GameBuilder};
+use crate::game_result::{GameResult, RoundType};
+use crate::logger::LogFile;
+use crate::reaction::Reflexes;
+use crate::signed_card::SignedCard;
+use crate::strategy::{PlayerStrategy, TableView};
+
+/* Decide en que orden llegan las cartas de una ronda rustica cuando no hay hilos que compitan.
+Recibe los jugadores que tiran, en orden de asiento, y devuelve esos mismos jugadores ordenados del
+mas rapido al mas lento. */
+pub trait ArrivalOrder {
+    fn arrival_order(&mut self, players: &[i32]) -> Vec<i32>;
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrivalModel {
+    /* Cualquier orden es igual de probable, como si todos reaccionaran igual de rapido. */
+    #[default]
+    Random,
+    /* Siempre llegan en orden de asiento. */
+    SeatOrder
+}
+
+impl ArrivalModel {
+    pub fn description(&self) -> &'static str {
+        return match self {
+            ArrivalModel::Random => "al azar",
+            ArrivalModel::SeatOrder => "orden de asiento"
+        };
+    }
+
+    pub fn build(&self, seed: u64) -> Box<dyn ArrivalOrder + Send> {
+        return match self {
+            ArrivalModel::Random => Box::new(RandomArrival{rng: StdRng::seed_from_u64(seed)}),
+            ArrivalModel::SeatOrder => Box::new(SeatOrderArrival)
+        };
+    }
+}
+
+impl std::str::FromStr for ArrivalModel {
+    type Err = String;
+
+    fn from_str(model: &str) -> Result<Self, Self::Err> {
+        return match model.trim().to_lowercase().replace('-', "_").as_str() {
+            "random" => Ok(ArrivalModel::Random),
+            "seat_order" => Ok(ArrivalModel::SeatOrder),
+            _ => Err(format!("Unknown arrival model '{}', expected random or seat_order.", model))
+        };
+    }
+}
+
+pub struct RandomArrival {
+    rng: StdRng
+}
+
+impl ArrivalOrder for RandomArrival {
+    fn arrival_order(&mut self, players: &[i32]) -> Vec<i32> {
+        let mut order = players.to_vec();
+        order.shuffle(&mut self.rng);
+        return order;
+    }
+}
+
+pub struct SeatOrderArrival;
+
+impl ArrivalOrder for SeatOrderArrival {
+    fn arrival_order(&mut self, players: &[i32]) -> Vec<i32> {
+        return players.to_vec();
+    }
+}
+
+/* Corre una llamada a la estrategia de un jugador. Si entra en panico se lo anota como caido y se
+devuelve None. */
+fn guarded<R, F: FnOnce() -> R>(state: &mut GameState, player: i32, call: F) -> std::io::Result<Option<R>> {
+    return match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(result) => Ok(Some(result)),
+        Err(payload) => {
+            state.player_crashed(player, &panic_message(payload.as_ref()))?;
+            Ok(None)
+        }
+    };
+}
+
+/* Vuelve a esperar en las rondas a los ausentes que ya terminaron la suya segun el reloj de la
+partida. Con wait, si estan todos ausentes, el reloj avanza hasta que vuelve el primero. */
+fn readmit_players(state: &mut GameState, returns: &mut HashMap<i32, Duration>, clock: &mut Duration,
+                   wait: bool) -> std::io::Result<()> {
+    if wait && state.everyone_absent() {
+        *clock = returns.values().copied().min().map_or(*clock, |first_return| first_return.max(*clock));
+    }
+    let mut returned: Vec<i32> = returns.iter()
+        .filter(|(_, returns_at)| **returns_at <= *clock)
+        .map(|(player, _)| *player)
+        .collect();
+    returned.sort();
+    for player in returned {
+        returns.remove(&player);
+        state.player_returned(player)?;
+    }
+    return Ok(());
+}
+
+/* Motor de un solo hilo: juega las mismas rondas que los otros motores pero le pide la carta a cada
+estrategia en orden de asiento. Las cartas de las rondas rusticas llegan segun el tiempo de reaccion
+de cada jugador y, entre los que tardan lo mismo, segun el modelo de llegada. Si hay tiempo limite,
+los que tardan mas que el llegan tarde y, como en los otros motores, se pierden las rondas que
+empiezan antes de que terminen de reaccionar. Para eso se lleva un reloj simulado: cada ronda rustica
+dura lo que tarda el ultimo en tirar, o el tiempo limite si alguno llega tarde, y las normales no
+llevan tiempo. Con la misma semilla siempre da el mismo resultado. */
+pub fn run(settings: GameSettings, mut strategies: Vec<Box<dyn PlayerStrategy + Send>>,
+           mut reflexes: Vec<Reflexes>, mut arrival: Box<dyn ArrivalOrder + Send>, logfile: LogFile) -> std::io::Result<GameResult> {
+    let (mut state, mut hands) = GameState::new(logfile, settings)?;
+    let players = state.players();
+    let mut cards_thrown = vec![0; players as usize];
+    let mut clock = Duration::ZERO;
+    /* Cuando termina de reaccionar cada ausente. */
+    let mut returns: HashMap<i32, Duration> = HashMap::new();
+    for (p, strategy) in strategies.iter_mut().enumerate() {
+        guarded(&mut state, p as i32, || strategy.deal(p as i32, &hands[p]))?;
+    }
+
+    while state.keep_playing() {
+        readmit_players(&mut state, &mut returns, &mut clock, true)?;
+        let round_type = state.start_round()?;
+        let mut cards: Vec<SignedCard> = Vec::new();
+        let mut thrown: Vec<SignedCard> = Vec::new();
+
+        for p in 0..players {
+            if state.is_eliminated(p) || state.is_absent(p) {
+                continue;
+            }
+            let turn = state.turn(p, &cards);
+            let seat = p as usize;
+            if let Some(last_round) = &turn.last_round {
+                let strategy = &mut strategies[seat];
+                if guarded(&mut state, p, || strategy.round_result(last_round))?.is_none() {
+                    continue;
+                }
+            }
+            if let Some(card) = turn.drawn_card {
+                hands[seat].insert(0, card);
+            }
+            if !turn.can_throw_card {
+                continue;
+            }
+            let table = TableView{cards_thrown: cards_thrown[seat], ..turn.table};
+            let (strategy, hand) = (&mut strategies[seat], &hands[seat]);
+            let chosen = match guarded(&mut state, p, || strategy.choose_card(hand, &table))? {
+                Some(chosen) if chosen < hand.len() => chosen,
+                Some(chosen) => {
+                    state.player_crashed(p, &format!("chose card {} from a hand of {}", chosen, hand.len()))?;
+                    continue;
+                }
+                None => continue
+            };
+            let card = hands[seat].remove(chosen);
+            cards_thrown[seat] += 1;
+            let signed_card = SignedCard{card, player_signature: p};
+            match round_type {
+                RoundType::Normal => cards.push(signed_card),
+                RoundType::Rustic => thrown.push(signed_card)
+            }
+        }
+
+        if round_type == RoundType::Rustic {
+            let throwing: Vec<i32> = thrown.iter().map(|signed_card| signed_card.player_signature).collect();
+            let reactions: HashMap<i32, Duration> = throwing.iter()
+                .map(|player| (*player, reflexes[*player as usize].react()))
+                .collect();
+            let mut order = arrival.arrival_order(&throwing);
+            order.sort_by_key(|player| reactions[player]);
+            let deadline = state.rustic_deadline();
+            let (on_time, late_players): (Vec<i32>, Vec<i32>) = order.into_iter()
+                .partition(|player| deadline.is_none_or(|deadline| reactions[player] <= deadline));
+            let round_time = match deadline {
+                Some(deadline) if !late_players.is_empty() => deadline,
+                _ => on_time.iter().map(|player| reactions[player]).max().unwrap_or_default()
+            };
+            for player in on_time {
+                let position = thrown.iter().position(|signed_card| signed_card.player_signature == player).unwrap();
+                cards.push(thrown.swap_remove(position));
+            }
+            for player in &late_players {
+                state.player_absent(*player)?;
+                returns.insert(*player, clock + reactions[player]);
+            }
+            state.register_late_players(late_players)?;
+            clock += round_time;
+        }
+        for signed_card in &cards {
+            state.register_card(signed_card)?;
+        }
+
+        state.finish_round(cards)?;
+    }
+    readmit_players(&mut state, &mut returns, &mut clock, false)?;
+
+    let abort_reason = state.abort_reason();
+    let eliminated_players: Vec<i32> = (0..players).filter(|p| state.is_eliminated(*p)).collect();
+    let game_result = state.finish_game()?;
+    let game_over = game_over(&game_result);
+    for (p, strategy) in strategies.iter_mut().enumerate() {
+        if eliminated_players.contains(&(p as i32)) {
+            continue;
+        }
+        /* La partida ya termino: si alguna estrategia falla al enterarse no cambia el resultado. */
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            if let Some(last_round) = &game_over.last_round {
+                strategy.round_result(last_round);
+            }
+            strategy.game_over(&game_over.points_by_user, &game_over.winners);
+        }));
+    }
+    if let Some(reason) = abort_reason {
+        return Err(std::io::Error::other(reason));
+    }
+    return Ok(game_result);
+}
+
+/* Juega una partida por semilla con el motor secuencial, repartiendo las partidas entre todos los
+nucleos. new_game arma cada partida a partir de su semilla; los resultados vuelven en el orden de
+seeds. */
+pub fn run_batch<F>(seeds: &[u64], new_game: F) -> Result<Vec<GameResult>, String>
+    where F: Fn(u64) -> GameBuilder + Sync {
+    let workers = std::thread::available_parallelism().map_or(1, |workers| workers.get());
+    let chunk_size = seeds.len().div_ceil(workers).max(1);
+    let new_game = &new_game;
+    let results: Vec<Result<Vec<GameResult>, String>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = seeds.chunks(chunk_size).map(|chunk| {
+            scope.spawn(move || {
+                return chunk.iter().map(|seed| {
+                    let game = new_game(*seed).seed(*seed).engine(Engine::Sequential).build()?;
+                    return game.run().map_err(|e| e.to_string());
+                }).collect::<Result<Vec<GameResult>, String>>();
+            })
+        }).collect();
+        return handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    });
+    return Ok(results.into_iter().collect::<Result<Vec<Vec<GameResult>>, String>>()?.concat());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+    use crate::ruleset::Ruleset;
+    use crate::strategy::StrategyKind;
+    use crate::reaction::ReactionTime;
+    use crate::coordinator::LatePolicy;
+
+    #[test]
+    fn test_arrival_models() {
+        assert_eq!(ArrivalModel::SeatOrder.build(1).arrival_order(&[0, 2, 3]), vec![0, 2, 3]);
+        let mut order = ArrivalModel::Random.build(1).arrival_order(&[0, 1, 2, 3, 4]);
+        assert_eq!(order, ArrivalModel::Random.build(1).arrival_order(&[0, 1, 2, 3, 4]));
+        order.sort();
+        assert_eq!(order, vec![0, 1, 2, 3, 4]);
+        assert_eq!("seat-order".parse::<ArrivalModel>(), Ok(ArrivalModel::SeatOrder));
+    }
+
+    #[test]
+    fn test_sequential_engine_is_deterministic() {
+        let play = || Game::builder(4).seed(21).strategy(StrategyKind::Random).engine(Engine::Sequential)
+            .build().unwrap().run().unwrap();
+        let first = play();
+        let second = play();
+        assert_eq!(first.points_by_user, second.points_by_user);
+        assert_eq!(first.rounds.len(), second.rounds.len());
+        for (first_round, second_round) in first.rounds.iter().zip(&second.rounds) {
+            assert_eq!(first_round.cards, second_round.cards);
+        }
+    }
+
+    #[test]
+    fn test_seat_order_arrival_makes_last_seat_slowest() {
+        let ruleset = Ruleset{normal_round_probability: 0.0, ..Ruleset::default()};
+        let result = Game::builder(3).seed(5).ruleset(ruleset).engine(Engine::Sequential)
+            .arrival(ArrivalModel::SeatOrder).build().unwrap().run().unwrap();
+        let first_round = &result.rounds[0];
+        assert_eq!(first_round.hand_outcome.fastest_player, Some(0));
+        assert_eq!(first_round.hand_outcome.slowest_player, Some(2));
+    }
+
+    #[test]
+    fn test_reaction_times_order_arrivals() {
+        let ruleset = Ruleset{normal_round_probability: 0.0, suspension_rounds: 0, ..Ruleset::default()};
+        let result = Game::builder(3).seed(5).ruleset(ruleset).engine(Engine::Sequential)
+            .reaction_time(ReactionTime::Normal{mean_ms: 300.0, std_dev_ms: 20.0})
+            .seat_reaction_time(1, ReactionTime::Uniform{min_ms: 10.0, max_ms: 20.0})
+            .seat_reaction_time(0, ReactionTime::Fixed{ms: 100.0})
+            .build().unwrap().run().unwrap();
+        let fastest = result.rounds.iter().filter(|round| round.hand_outcome.fastest_player == Some(1)).count();
+        assert_eq!(fastest, result.rounds.len());
+    }
+
+    #[test]
+    fn test_late_players_forfeit_their_card() {
+        let ruleset = Ruleset{normal_round_probability: 0.0, suspension_rounds: 0, rustic_deadline_ms: Some(500),
+                              late_policy: LatePolicy::Forfeit, ..Ruleset::default()};
+        let result = Game::builder(3).seed(5).ruleset(ruleset).engine(Engine::Sequential)
+            .seat_reaction_time(0, ReactionTime::Fixed{ms: 300.0})
+            .seat_reaction_time(1, ReactionTime::Fixed{ms: 400.0})
+            .seat_reaction_time(2, ReactionTime::Fixed{ms: 800.0})
+            .build().unwrap().run().unwrap();
+        /* El motor secuencial no duerme, asi que los tiempos no dependen del planificador. El jugador 2
+        termina de reaccionar a los 800 ms, durante la ronda siguiente a la que llego tarde: se pierde
+        esa ronda y vuelve en la otra. */
+        for (i, round) in result.rounds.iter().enumerate() {
+            if i % 2 == 0 {
+                assert_eq!(round.late_players, vec![2]);
+            } else {
+                assert!(round.late_players.is_empty());
+            }
+            assert!(round.cards.iter().all(|signed_card| signed_card.player_signature != 2));
+            assert_ne!(round.hand_outcome.slowest_player, Some(2));
+            assert!(!round.hand_outcome.earned_points.contains_key(&2));
+        }
+    }
+
+    #[test]
+    fn test_sequential_engine_scores_like_threads() {
+        let ruleset = Ruleset{normal_round_probability: 1.0, ..Ruleset::default()};
+        let play = |engine| Game::builder(4).seed(3).ruleset(ruleset).strategy(StrategyKind::SaveHighCards)
+            .engine(engine).build().unwrap().run().unwrap();
+        let threads = play(Engine::Threads);
+        let sequential = play(Engine::Sequential);
+        assert_eq!(sequential.points_by_user, threads.points_by_user);
+        assert_eq!(sequential.winners, threads.winners);
+    }
+
+    #[test]
+    fn test_run_batch() {
+        let seeds: Vec<u64> = (0..20).collect();
+        let results = run_batch(&seeds, |_| Game::builder(4).strategy(StrategyKind::Random)).unwrap();
+        assert_eq!(results.len(), 20);
+        for (seed, result) in seeds.iter().zip(&results) {
+            assert_eq!(result.seed, *seed);
+            let alone = Game::builder(4).strategy(StrategyKind::Random).seed(*seed).engine(Engine::Sequential)
+                .build().unwrap().run().unwrap();
+            assert_eq!(result.points_by_user, alone.points_by_user);
+        }
+        assert!(run_batch(&seeds, |_| Game::builder(1)).is_err());
+    }
+}
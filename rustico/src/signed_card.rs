@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use crate::cards::Card;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SignedCard {
     pub card: Card,
     pub player_signature: i32
@@ -0,0 +1,247 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use crate::cards::Card;
+use crate::ruleset::Ruleset;
+use crate::signed_card::SignedCard;
+use crate::round_scorer::Points;
+use crate::game_result::{RoundResult, RoundType};
+
+/* Lo que un jugador ve de la partida cuando le toca tirar. */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableView {
+    pub player_id: i32,
+    /* Cantidad de cartas que el jugador ya tiro en la partida. */
+    pub cards_thrown: i32,
+    pub round: i32,
+    pub round_type: RoundType,
+    /* Cartas que ya se tiraron en esta ronda, en el orden en que llegaron. */
+    pub cards_played: Vec<SignedCard>,
+    /* Puntos de cada jugador al empezar la ronda. */
+    pub points_by_user: HashMap<i32, Points>,
+    pub suspended_players: Vec<i32>
+}
+
+/* Decide que carta tira un jugador en cada turno. Devuelve la posicion de la carta elegida dentro de
+hand, que nunca esta vacia. */
+pub trait PlayerStrategy {
+    fn choose_card(&mut self, hand: &[Card], table: &TableView) -> usize;
+
+    /* Avisos de la partida para las estrategias que llevan su propio estado. Por defecto se ignoran. */
+    fn deal(&mut self, _player_id: i32, _hand: &[Card]) {}
+
+    fn round_result(&mut self, _round: &RoundResult) {}
+
+    fn game_over(&mut self, _points_by_user: &HashMap<i32, Points>, _winners: &[i32]) {}
+
+    fn description(&self) -> String {
+        return "personalizada".to_string();
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyKind {
+    #[default]
+    DealtOrder,
+    Random,
+    HighestFirst,
+    LowestFirst,
+    SaveHighCards
+}
+
+impl StrategyKind {
+    /* Nombre que acepta --strategy. */
+    pub fn name(&self) -> &'static str {
+        return match self {
+            StrategyKind::DealtOrder => "dealt_order",
+            StrategyKind::Random => "random",
+            StrategyKind::HighestFirst => "highest_first",
+            StrategyKind::LowestFirst => "lowest_first",
+            StrategyKind::SaveHighCards => "save_high_cards"
+        };
+    }
+
+    pub fn description(&self) -> &'static str {
+        return match self {
+            StrategyKind::DealtOrder => "orden de reparto",
+            StrategyKind::Random => "al azar",
+            StrategyKind::HighestFirst => "mas alta primero",
+            StrategyKind::LowestFirst => "mas baja primero",
+            StrategyKind::SaveHighCards => "guardar cartas altas"
+        };
+    }
+
+    /* La semilla solo la usa la estrategia al azar, para que la partida se pueda reproducir. */
+    pub fn build(&self, ruleset: Ruleset, seed: u64) -> Box<dyn PlayerStrategy + Send> {
+        return match self {
+            StrategyKind::DealtOrder => Box::new(DealtOrder),
+            StrategyKind::Random => Box::new(RandomStrategy{rng: StdRng::seed_from_u64(seed)}),
+            StrategyKind::HighestFirst => Box::new(HighestFirst{ruleset}),
+            StrategyKind::LowestFirst => Box::new(LowestFirst{ruleset}),
+            StrategyKind::SaveHighCards => Box::new(SaveHighCards{ruleset})
+        };
+    }
+}
+
+impl std::str::FromStr for StrategyKind {
+    type Err = String;
+
+    fn from_str(strategy: &str) -> Result<Self, Self::Err> {
+        return match strategy.trim().to_lowercase().replace('-', "_").as_str() {
+            "dealt_order" => Ok(StrategyKind::DealtOrder),
+            "random" => Ok(StrategyKind::Random),
+            "highest_first" => Ok(StrategyKind::HighestFirst),
+            "lowest_first" => Ok(StrategyKind::LowestFirst),
+            "save_high_cards" => Ok(StrategyKind::SaveHighCards),
+            _ => Err(format!("Unknown strategy '{}', expected dealt_order, random, highest_first, lowest_first \
+                              or save_high_cards.", strategy))
+        };
+    }
+}
+
+/* Tira las cartas en el orden en que las recibio. */
+pub struct DealtOrder;
+
+impl PlayerStrategy for DealtOrder {
+    fn choose_card(&mut self, _hand: &[Card], _table: &TableView) -> usize {
+        return 0;
+    }
+
+    fn description(&self) -> String {
+        return StrategyKind::DealtOrder.description().to_string();
+    }
+}
+
+pub struct RandomStrategy {
+    rng: StdRng
+}
+
+impl PlayerStrategy for RandomStrategy {
+    fn choose_card(&mut self, hand: &[Card], _table: &TableView) -> usize {
+        return self.rng.gen_range(0, hand.len());
+    }
+
+    fn description(&self) -> String {
+        return StrategyKind::Random.description().to_string();
+    }
+}
+
+fn strongest(hand: &[Card], ruleset: &Ruleset) -> usize {
+    return (0..hand.len()).max_by_key(|i| ruleset.card_strength(&hand[*i])).unwrap();
+}
+
+fn weakest(hand: &[Card], ruleset: &Ruleset) -> usize {
+    return (0..hand.len()).min_by_key(|i| ruleset.card_strength(&hand[*i])).unwrap();
+}
+
+pub struct HighestFirst {
+    ruleset: Ruleset
+}
+
+impl PlayerStrategy for HighestFirst {
+    fn choose_card(&mut self, hand: &[Card], _table: &TableView) -> usize {
+        return strongest(hand, &self.ruleset);
+    }
+
+    fn description(&self) -> String {
+        return StrategyKind::HighestFirst.description().to_string();
+    }
+}
+
+pub struct LowestFirst {
+    ruleset: Ruleset
+}
+
+impl PlayerStrategy for LowestFirst {
+    fn choose_card(&mut self, hand: &[Card], _table: &TableView) -> usize {
+        return weakest(hand, &self.ruleset);
+    }
+
+    fn description(&self) -> String {
+        return StrategyKind::LowestFirst.description().to_string();
+    }
+}
+
+/* Cartas que SaveHighCards guarda para el final: de la J para arriba segun el orden de la partida. */
+const HIGH_CARD_RANK :i32 = 11;
+
+/* Mientras le queden cartas bajas tira la mas alta de ellas, asi compite por la carta maxima sin gastar
+las altas; cuando solo le quedan cartas altas las tira de mayor a menor. */
+pub struct SaveHighCards {
+    ruleset: Ruleset
+}
+
+impl PlayerStrategy for SaveHighCards {
+    fn choose_card(&mut self, hand: &[Card], _table: &TableView) -> usize {
+        let low_cards: Vec<usize> = (0..hand.len())
+            .filter(|i| self.ruleset.rank_order.rank(&hand[*i]) < HIGH_CARD_RANK)
+            .collect();
+        return match low_cards.iter().max_by_key(|i| self.ruleset.card_strength(&hand[**i])) {
+            Some(i) => *i,
+            None => strongest(hand, &self.ruleset)
+        };
+    }
+
+    fn description(&self) -> String {
+        return StrategyKind::SaveHighCards.description().to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{CardSuit, RankOrder};
+
+    fn hand() -> Vec<Card> {
+        return vec![Card{number: 5, suit: CardSuit::Spades}, Card{number: 1, suit: CardSuit::Hearts},
+                    Card{number: 12, suit: CardSuit::Clubs}, Card{number: 9, suit: CardSuit::Diamonds}];
+    }
+
+    fn choose(kind: StrategyKind, ruleset: Ruleset, hand: &[Card]) -> Card {
+        let mut strategy = kind.build(ruleset, 1);
+        return hand[strategy.choose_card(hand, &TableView::default())];
+    }
+
+    #[test]
+    fn test_dealt_order() {
+        assert_eq!(choose(StrategyKind::DealtOrder, Ruleset::default(), &hand()), hand()[0]);
+    }
+
+    #[test]
+    fn test_highest_and_lowest_first_follow_rank_order() {
+        let ace_high = Ruleset{rank_order: RankOrder::AceHigh, ..Ruleset::default()};
+        assert_eq!(choose(StrategyKind::HighestFirst, Ruleset::default(), &hand()).number, 12);
+        assert_eq!(choose(StrategyKind::HighestFirst, ace_high, &hand()).number, 1);
+        assert_eq!(choose(StrategyKind::LowestFirst, Ruleset::default(), &hand()).number, 1);
+        assert_eq!(choose(StrategyKind::LowestFirst, ace_high, &hand()).number, 5);
+    }
+
+    #[test]
+    fn test_save_high_cards() {
+        assert_eq!(choose(StrategyKind::SaveHighCards, Ruleset::default(), &hand()).number, 9);
+        let high_cards = vec![Card{number: 11, suit: CardSuit::Spades}, Card{number: 13, suit: CardSuit::Hearts}];
+        assert_eq!(choose(StrategyKind::SaveHighCards, Ruleset::default(), &high_cards).number, 13);
+    }
+
+    #[test]
+    fn test_random_strategy_is_reproducible() {
+        let mut first = StrategyKind::Random.build(Ruleset::default(), 7);
+        let mut second = StrategyKind::Random.build(Ruleset::default(), 7);
+        for _ in 0..10 {
+            let choice = first.choose_card(&hand(), &TableView::default());
+            assert!(choice < hand().len());
+            assert_eq!(choice, second.choose_card(&hand(), &TableView::default()));
+        }
+    }
+
+    #[test]
+    fn test_parse_strategy() {
+        assert_eq!("save-high-cards".parse::<StrategyKind>(), Ok(StrategyKind::SaveHighCards));
+        assert!("cheat".parse::<StrategyKind>().is_err());
+        assert_eq!(StrategyKind::LowestFirst.name().parse::<StrategyKind>(), Ok(StrategyKind::LowestFirst));
+    }
+}
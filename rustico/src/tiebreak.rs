@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::game_result::RoundResult;
+use crate::cards::Card;
+use crate::round_scorer::Points;
+use crate::ruleset::Ruleset;
+
+/* Como se decide el ganador cuando varios jugadores terminan con el puntaje maximo. Si el criterio
+elegido no alcanza para separarlos, los que siguen empatados son co-ganadores. */
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TiebreakPolicy {
+    #[default]
+    CoWinners,
+    MostMaxCards,
+    FewestSuspensions,
+    SuddenDeath
+}
+
+impl TiebreakPolicy {
+    pub fn description(&self) -> &'static str {
+        return match self {
+            TiebreakPolicy::CoWinners => "co-ganadores",
+            TiebreakPolicy::MostMaxCards => "mas cartas maximas",
+            TiebreakPolicy::FewestSuspensions => "menos suspensiones",
+            TiebreakPolicy::SuddenDeath => "muerte subita"
+        };
+    }
+}
+
+impl std::str::FromStr for TiebreakPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        return match policy.trim().to_lowercase().replace('-', "_").as_str() {
+            "co_winners" => Ok(TiebreakPolicy::CoWinners),
+            "most_max_cards" => Ok(TiebreakPolicy::MostMaxCards),
+            "fewest_suspensions" => Ok(TiebreakPolicy::FewestSuspensions),
+            "sudden_death" => Ok(TiebreakPolicy::SuddenDeath),
+            _ => Err(format!("Unknown tiebreak policy '{}', expected co_winners, most_max_cards, \
+                              fewest_suspensions or sudden_death.", policy))
+        };
+    }
+}
+
+/* Jugadores ganadores y el criterio que los decidio. decided_by es None cuando no hubo empate. */
+pub struct Tiebreak {
+    pub winners: Vec<i32>,
+    pub decided_by: Option<TiebreakPolicy>
+}
+
+pub fn players_with_max_points(points_by_user: &HashMap<i32, Points>) -> Vec<i32> {
+    let max_points = points_by_user.values().max().unwrap();
+    let mut leaders: Vec<i32> = points_by_user.iter()
+        .filter(|(_, points)| *points == max_points)
+        .map(|(player, _)| *player)
+        .collect();
+    leaders.sort();
+    return leaders;
+}
+
+fn best_by_key<F: Fn(i32) -> i32>(tied: &[i32], key: F) -> Vec<i32> {
+    let best = tied.iter().map(|player| key(*player)).max().unwrap();
+    return tied.iter().copied().filter(|player| key(*player) == best).collect();
+}
+
+fn count_by_user<F: Fn(&RoundResult) -> Vec<i32>>(rounds: &[RoundResult], players_in_round: F) -> HashMap<i32, i32> {
+    let mut counts = HashMap::new();
+    for round in rounds {
+        for player in players_in_round(round) {
+            *counts.entry(player).or_insert(0) += 1;
+        }
+    }
+    return counts;
+}
+
+/* Cada jugador empatado recibe una carta; los que sacan la mas alta segun las reglas de la partida
+siguen jugando hasta que quede uno solo. Las cartas salen primero de tiebreak_deck mientras alcancen
+para todos y despues de un mazo nuevo en cada vuelta. */
+fn sudden_death<R: Rng>(tied: &[i32], ruleset: &Ruleset, tiebreak_deck: &[Card], rng: &mut R) -> Vec<i32> {
+    let mut remaining = tied.to_vec();
+    let mut tiebreak_cards = tiebreak_deck.iter().copied();
+    while remaining.len() > 1 {
+        let mut deck: Vec<Card> = tiebreak_cards.by_ref().take(remaining.len()).collect();
+        if deck.len() < remaining.len() {
+            deck = ruleset.deck.for_players(remaining.len() as i32).build(rng);
+        }
+        let strengths: HashMap<i32, (i32, i32)> = remaining.iter().zip(deck.iter())
+            .map(|(player, card)| (*player, ruleset.card_strength(card)))
+            .collect();
+        let best = strengths.values().max().copied().unwrap();
+        remaining = remaining.iter().copied().filter(|player| strengths[player] == best).collect();
+    }
+    return remaining;
+}
+
+pub fn break_tie<R: Rng>(tied: Vec<i32>, ruleset: &Ruleset, rounds: &[RoundResult], tiebreak_deck: &[Card],
+                         rng: &mut R) -> Tiebreak {
+    if tied.len() == 1 {
+        return Tiebreak{winners: tied, decided_by: None};
+    }
+
+    let policy = ruleset.tiebreak;
+    let winners = match policy {
+        TiebreakPolicy::CoWinners => tied,
+        TiebreakPolicy::MostMaxCards => {
+            let max_cards = count_by_user(rounds, |round| {
+                round.hand_outcome.players_with_max_card.iter().copied().collect()
+            });
+            best_by_key(&tied, |player| *max_cards.get(&player).unwrap_or(&0))
+        },
+        TiebreakPolicy::FewestSuspensions => {
+            /* Cuenta las veces que el jugador fue penalizado, no las rondas que paso suspendido:
+            una penalizacion cerca del final puede cumplirse solo en parte. */
+            let penalties = count_by_user(rounds, |round| round.hand_outcome.slowest_player.into_iter().collect());
+            best_by_key(&tied, |player| -*penalties.get(&player).unwrap_or(&0))
+        },
+        TiebreakPolicy::SuddenDeath => sudden_death(&tied, ruleset, tiebreak_deck, rng)
+    };
+
+    let decided_by = if winners.len() == 1 { policy } else { TiebreakPolicy::CoWinners };
+    return Tiebreak{winners, decided_by: Some(decided_by)};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::cards::{CardSuit, random_full_deck};
+    use crate::game_result::RoundType;
+    use crate::round_scorer::{HandOutcome, points};
+
+    fn round(players_with_max_card: Vec<i32>, slowest_player: Option<i32>, suspended_players: Vec<i32>) -> RoundResult {
+        let hand_outcome = HandOutcome{earned_points: HashMap::new(), max_card_points: points(10),
+                                       carried_points: points(0),
+                                       players_with_max_card: players_with_max_card.into_iter().collect::<HashSet<i32>>(),
+                                       slowest_player, fastest_player: None};
+        return RoundResult{round: 1, round_type: RoundType::Normal, cards: vec![], hand_outcome, suspended_players,
+                           late_players: vec![]};
+    }
+
+    fn rules(tiebreak: TiebreakPolicy) -> Ruleset {
+        return Ruleset{tiebreak, ..Ruleset::default()};
+    }
+
+    #[test]
+    fn test_players_with_max_points() {
+        let mut points_by_user = HashMap::new();
+        points_by_user.insert(0, points(10));
+        points_by_user.insert(1, points(25));
+        points_by_user.insert(2, points(25));
+        assert_eq!(players_with_max_points(&points_by_user), vec![1, 2]);
+        points_by_user.insert(2, points(24));
+        assert_eq!(players_with_max_points(&points_by_user), vec![1]);
+    }
+
+    #[test]
+    fn test_single_leader_needs_no_tiebreak() {
+        let tiebreak = break_tie(vec![3], &rules(TiebreakPolicy::SuddenDeath), &[], &[], &mut StdRng::seed_from_u64(1));
+        assert_eq!(tiebreak.winners, vec![3]);
+        assert_eq!(tiebreak.decided_by, None);
+    }
+
+    #[test]
+    fn test_co_winners() {
+        let tiebreak = break_tie(vec![1, 2], &rules(TiebreakPolicy::CoWinners), &[], &[], &mut StdRng::seed_from_u64(1));
+        assert_eq!(tiebreak.winners, vec![1, 2]);
+        assert_eq!(tiebreak.decided_by, Some(TiebreakPolicy::CoWinners));
+    }
+
+    #[test]
+    fn test_most_max_cards() {
+        let rounds = vec![round(vec![1], None, vec![]), round(vec![1, 2], None, vec![]), round(vec![0], None, vec![])];
+        let tiebreak = break_tie(vec![0, 1, 2], &rules(TiebreakPolicy::MostMaxCards), &rounds, &[], &mut StdRng::seed_from_u64(1));
+        assert_eq!(tiebreak.winners, vec![1]);
+        assert_eq!(tiebreak.decided_by, Some(TiebreakPolicy::MostMaxCards));
+    }
+
+    #[test]
+    fn test_fewest_suspensions_falls_back_to_co_winners() {
+        let rounds = vec![round(vec![], Some(0), vec![]), round(vec![], None, vec![0]),
+                          round(vec![], Some(3), vec![]), round(vec![], None, vec![3])];
+        let tiebreak = break_tie(vec![0, 1, 2], &rules(TiebreakPolicy::FewestSuspensions), &rounds, &[], &mut StdRng::seed_from_u64(1));
+        assert_eq!(tiebreak.winners, vec![1, 2]);
+        assert_eq!(tiebreak.decided_by, Some(TiebreakPolicy::CoWinners));
+    }
+
+    #[test]
+    fn test_fewest_suspensions_counts_penalties() {
+        /* Con dos rondas de suspension, el jugador 1 es penalizado dos veces pero la segunda es en la
+        ultima ronda, asi que los dos pasan la misma cantidad de rondas suspendidos. */
+        let ruleset = Ruleset{suspension_rounds: 2, ..rules(TiebreakPolicy::FewestSuspensions)};
+        let rounds = vec![round(vec![], Some(0), vec![]), round(vec![], None, vec![0]),
+                          round(vec![], Some(1), vec![0]), round(vec![], None, vec![1]),
+                          round(vec![], None, vec![1]), round(vec![], Some(1), vec![])];
+        let tiebreak = break_tie(vec![0, 1], &ruleset, &rounds, &[], &mut StdRng::seed_from_u64(1));
+        assert_eq!(tiebreak.winners, vec![0]);
+        assert_eq!(tiebreak.decided_by, Some(TiebreakPolicy::FewestSuspensions));
+    }
+
+    #[test]
+    fn test_sudden_death_always_picks_one_winner() {
+        for seed in 0..20 {
+            let tiebreak = break_tie(vec![0, 1, 2, 3], &rules(TiebreakPolicy::SuddenDeath), &[], &[], &mut StdRng::seed_from_u64(seed));
+            assert_eq!(tiebreak.winners.len(), 1);
+            assert_eq!(tiebreak.decided_by, Some(TiebreakPolicy::SuddenDeath));
+        }
+    }
+
+    #[test]
+    fn test_sudden_death_with_suit_precedence_takes_one_round() {
+        let ruleset = Ruleset{suit_precedence: Some([CardSuit::Spades, CardSuit::Hearts,
+                                                     CardSuit::Diamonds, CardSuit::Clubs]),
+                              ..rules(TiebreakPolicy::SuddenDeath)};
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut expected_rng = rng.clone();
+        let tiebreak = break_tie(vec![0, 1, 2, 3], &ruleset, &[], &[], &mut rng);
+        let deck = random_full_deck(&mut expected_rng);
+        let best = (0..4).max_by_key(|player| ruleset.card_strength(&deck[*player as usize])).unwrap();
+        assert_eq!(tiebreak.winners, vec![best]);
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("sudden-death".parse::<TiebreakPolicy>(), Ok(TiebreakPolicy::SuddenDeath));
+        assert_eq!("most_max_cards".parse::<TiebreakPolicy>(), Ok(TiebreakPolicy::MostMaxCards));
+        assert!("coin_flip".parse::<TiebreakPolicy>().is_err());
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+use crate::cards::Card;
+use crate::game_result::RoundResult;
+use crate::round_scorer::Points;
+use crate::signed_card::SignedCard;
+use crate::strategy::TableView;
+
+/* Aviso de que le toca jugar a un jugador. */
+#[derive(Clone, Debug)]
+pub struct Turn {
+    /* false si el jugador esta suspendido y no tira carta en esta ronda. */
+    pub can_throw_card: bool,
+    /* Carta robada del pozo por haber sido el mas rapido de la ultima ronda rustica. Se agrega al
+    principio de la mano. */
+    pub drawn_card: Option<Card>,
+    /* Lo que el jugador puede ver de la mesa en este turno. */
+    pub table: TableView,
+    /* Resultado de la ronda anterior, None en la primera. */
+    pub last_round: Option<RoundResult>
+}
+
+#[derive(Clone, Debug)]
+pub struct GameOver {
+    pub points_by_user: HashMap<i32, Points>,
+    pub last_round: Option<RoundResult>,
+    pub winners: Vec<i32>
+}
+
+#[derive(Clone, Debug)]
+pub enum Notification {
+    Turn(Turn),
+    GameOver(GameOver)
+}
+
+/* Lo que le llega al coordinador de los jugadores: una carta o el aviso de que un jugador se cayo. */
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerEvent {
+    Card(SignedCard),
+    Crashed { player_id: i32, reason: String }
+}
+
+/* Como se comunica el coordinador con los jugadores. En cada ronda ambos lados se sincronizan con
+sync_round antes y despues de jugar: en las rondas normales el coordinador se sincroniza y despues
+avisa el turno a cada jugador y espera su carta, en las rusticas avisa a todos antes de sincronizarse
+para que salgan a la vez. Al final se sincroniza, avisa el fin y se sincroniza una ultima vez.
+Las esperas son futures para que el mismo coordinador corra en un hilo (con block_on) o en una tarea
+de tokio; los avisos no esperan a nadie. */
+pub trait CoordinatorTransport {
+    fn deal(&mut self, player_id: i32, hand: Vec<Card>);
+
+    fn notify_turn(&mut self, player_id: i32, turn: Turn);
+
+    /* Devuelve las cartas en el orden en que llegan, junto con los avisos de los jugadores que se
+    caen. */
+    fn receive_card(&mut self) -> impl Future<Output = PlayerEvent> + Send;
+
+    /* Como receive_card pero espera a lo sumo timeout. None si no llego nada. */
+    fn receive_card_timeout(&mut self, timeout: Duration) -> impl Future<Output = Option<PlayerEvent>> + Send;
+
+    fn sync_round(&mut self) -> impl Future<Output = ()> + Send;
+
+    /* Deja de esperar en sync_round a un jugador que no tiro antes del tiempo limite, para que la
+    proxima ronda empiece sin el. false si el jugador ya estaba sincronizado y no hace falta. */
+    fn excuse(&mut self, player_id: i32) -> bool;
+
+    /* Vuelve a esperar a los jugadores excusados que ya terminaron su ronda y devuelve sus asientos.
+    Con wait, si hay excusados, espera a que vuelva o se caiga alguno. */
+    fn readmit(&mut self, wait: bool) -> impl Future<Output = Vec<i32>> + Send;
+
+    fn end_game(&mut self, player_id: i32, game_over: GameOver);
+}
+
+/* Lado del jugador de CoordinatorTransport. Los pasos fallan si se pierde el contacto con el
+coordinador, por ejemplo si un cliente remoto se queda sin conexion. */
+pub trait PlayerTransport {
+    fn receive_hand(&mut self) -> impl Future<Output = std::io::Result<Vec<Card>>> + Send;
+
+    /* Espera el proximo aviso del coordinador. */
+    fn wait_turn(&mut self) -> impl Future<Output = std::io::Result<Notification>> + Send;
+
+    fn send_card(&mut self, signed_card: SignedCard) -> std::io::Result<()>;
+
+    fn sync_round(&mut self) -> impl Future<Output = std::io::Result<()>> + Send;
+
+    /* Espera el tiempo de reaccion del jugador. */
+    fn sleep(&mut self, duration: Duration) -> impl Future<Output = ()> + Send;
+
+    /* Corre una llamada que puede bloquear, como pedirle la carta a la estrategia. */
+    fn block<R, F: FnOnce() -> R>(&mut self, call: F) -> R {
+        return call();
+    }
+}
+
+/* Corre un future de los transportes que bloquean el hilo mientras esperan. Sus esperas nunca quedan
+pendientes, asi que alcanza con hacer poll hasta que termine. */
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        thread::yield_now();
+    }
+}
+
+pub(crate) fn coordinator_left<E>(_: E) -> std::io::Error {
+    return std::io::Error::other("The coordinator left the game.");
+}
+
+/* Buzon de avisos de un jugador: el coordinador deja el aviso y el jugador lo toma. */
+type Mailbox = Arc<(Mutex<Option<Notification>>, Condvar)>;
+
+/* Estado de una barrera de rondas, compartido por la de hilos y la de tokio. A diferencia de
+std::sync::Barrier se le pueden quitar participantes, para que el coordinador y el resto de los
+jugadores no se queden esperando a uno que se cayo o que no tiro a tiempo. */
+pub(crate) struct BarrierState {
+    participants: usize,
+    arrived: usize,
+    generation: u64,
+    arrived_seats: HashSet<i32>,
+    /* Asientos que el coordinador dejo de esperar porque no tiraron antes del tiempo limite. */
+    excused: HashSet<i32>,
+    /* Excusados que ya terminaron su ronda y esperan que el coordinador los vuelva a sumar. */
+    returning: HashSet<i32>,
+    closed: bool
+}
+
+/* Lo que tiene que esperar el que llega a la barrera. */
+pub(crate) enum Arrival {
+    Released,
+    /* Que se libere la vuelta en la que llego. */
+    Generation(u64),
+    /* Que el coordinador lo vuelva a sumar. */
+    Readmission(i32)
+}
+
+impl BarrierState {
+    pub(crate) fn new(participants: usize) -> Self {
+        return BarrierState{participants, arrived: 0, generation: 0, arrived_seats: HashSet::new(),
+                            excused: HashSet::new(), returning: HashSet::new(), closed: false};
+    }
+
+    fn release_if_complete(&mut self) -> bool {
+        if self.arrived == 0 || self.arrived < self.participants {
+            return false;
+        }
+        self.arrived = 0;
+        self.arrived_seats.clear();
+        self.generation += 1;
+        return true;
+    }
+
+    /* seat es None para el coordinador. */
+    pub(crate) fn arrive(&mut self, seat: Option<i32>) -> Arrival {
+        if self.closed {
+            return Arrival::Released;
+        }
+        if let Some(seat) = seat.filter(|seat| self.excused.contains(seat)) {
+            self.returning.insert(seat);
+            return Arrival::Readmission(seat);
+        }
+        self.arrived += 1;
+        self.arrived_seats.extend(seat);
+        let generation = self.generation;
+        return if self.release_if_complete() { Arrival::Released } else { Arrival::Generation(generation) };
+    }
+
+    pub(crate) fn is_released(&self, arrival: &Arrival) -> bool {
+        return self.closed || match arrival {
+            Arrival::Released => true,
+            Arrival::Generation(generation) => self.generation != *generation,
+            Arrival::Readmission(seat) => !self.returning.contains(seat)
+        };
+    }
+
+    /* false si el jugador ya habia llegado a la barrera en esta vuelta y no hace falta excusarlo. */
+    pub(crate) fn excuse(&mut self, seat: i32) -> bool {
+        if self.closed || self.arrived_seats.contains(&seat) || !self.excused.insert(seat) {
+            return false;
+        }
+        self.participants -= 1;
+        self.release_if_complete();
+        return true;
+    }
+
+    pub(crate) fn readmit(&mut self) -> Vec<i32> {
+        let mut returned: Vec<i32> = self.returning.drain().collect();
+        returned.sort();
+        for seat in &returned {
+            self.excused.remove(seat);
+        }
+        self.participants += returned.len();
+        return returned;
+    }
+
+    /* true si ya no hace falta esperar a que vuelva un excusado: volvio alguno, se cayo alguno o no
+    hay ninguno. */
+    pub(crate) fn can_readmit(&self, excused_before: usize) -> bool {
+        return self.closed || !self.returning.is_empty() || self.excused.len() < excused_before;
+    }
+
+    pub(crate) fn excused_count(&self) -> usize {
+        return self.excused.len();
+    }
+
+    pub(crate) fn is_excused(&self, seat: i32) -> bool {
+        return self.excused.contains(&seat);
+    }
+
+    /* Saca a un participante. Si los demas ya estaban esperando, los libera. */
+    pub(crate) fn leave(&mut self, seat: i32) {
+        if self.excused.remove(&seat) {
+            self.returning.remove(&seat);
+            return;
+        }
+        self.participants -= 1;
+        self.release_if_complete();
+    }
+
+    /* Termino la partida: nadie vuelve a esperar. */
+    pub(crate) fn close(&mut self) {
+        self.closed = true;
+    }
+}
+
+/* Barrera de las rondas para el motor con hilos. */
+pub struct RoundBarrier {
+    state: Mutex<BarrierState>,
+    cvar: Condvar
+}
+
+impl RoundBarrier {
+    pub fn new(participants: usize) -> Self {
+        return RoundBarrier{state: Mutex::new(BarrierState::new(participants)), cvar: Condvar::new()};
+    }
+
+    /* Corre un cambio de estado y despierta a los que esperan para que vuelvan a mirar. */
+    fn update<R, F: FnOnce(&mut BarrierState) -> R>(&self, change: F) -> R {
+        let result = change(&mut self.state.lock().unwrap());
+        self.cvar.notify_all();
+        return result;
+    }
+
+    pub fn wait(&self, seat: Option<i32>) {
+        let mut state = self.state.lock().unwrap();
+        let arrival = state.arrive(seat);
+        self.cvar.notify_all();
+        while !state.is_released(&arrival) {
+            state = self.cvar.wait(state).unwrap();
+        }
+    }
+
+    pub fn excuse(&self, seat: i32) -> bool {
+        return self.update(|state| state.excuse(seat));
+    }
+
+    /* Con wait, si hay excusados, espera a que vuelva o se caiga alguno. */
+    pub fn readmit(&self, wait: bool) -> Vec<i32> {
+        let mut state = self.state.lock().unwrap();
+        let excused_before = state.excused_count();
+        while wait && excused_before > 0 && !state.can_readmit(excused_before) {
+            state = self.cvar.wait(state).unwrap();
+        }
+        let returned = state.readmit();
+        self.cvar.notify_all();
+        return returned;
+    }
+
+    pub fn is_excused(&self, seat: i32) -> bool {
+        return self.state.lock().unwrap().is_excused(seat);
+    }
+
+    pub fn leave(&self, seat: i32) {
+        self.update(|state| state.leave(seat));
+    }
+
+    pub fn close(&self) {
+        self.update(|state| state.close());
+    }
+}
+
+/* Avisa al coordinador que un jugador se cayo y lo saca de la barrera. Se obtiene antes de lanzar al
+jugador porque su transporte se pierde con el panico. */
+pub struct CrashReporter {
+    event_sender: Sender<PlayerEvent>,
+    barrier: Arc<RoundBarrier>
+}
+
+impl CrashReporter {
+    pub fn report(&self, player_id: i32, reason: String) {
+        /* Si el coordinador ya termino no hay a quien avisarle. */
+        let _ = self.event_sender.send(PlayerEvent::Crashed{player_id, reason});
+        self.barrier.leave(player_id);
+    }
+}
+
+/* Transporte entre hilos del mismo proceso: las manos y las cartas viajan por canales, las rondas se
+sincronizan con una barrera y cada jugador espera su turno en una condition variable. */
+pub struct InProcessCoordinator {
+    tx_hands: HashMap<i32, Sender<Vec<Card>>>,
+    card_receiver: Receiver<PlayerEvent>,
+    barrier: Arc<RoundBarrier>,
+    mailboxes: HashMap<i32, Mailbox>
+}
+
+pub struct InProcessPlayer {
+    seat: i32,
+    rx_hand: Receiver<Vec<Card>>,
+    card_sender: Sender<PlayerEvent>,
+    barrier: Arc<RoundBarrier>,
+    mailbox: Mailbox
+}
+
+pub fn in_process(players: i32) -> (InProcessCoordinator, Vec<InProcessPlayer>) {
+    let barrier = Arc::new(RoundBarrier::new((players + 1) as usize));
+    let (card_sender, card_receiver) = mpsc::channel();
+    let mut tx_hands = HashMap::new();
+    let mut mailboxes = HashMap::new();
+    let mut player_transports = Vec::new();
+    for p in 0..players {
+        let (tx_hand, rx_hand) = mpsc::channel();
+        let mailbox: Mailbox = Arc::new((Mutex::new(None), Condvar::new()));
+        tx_hands.insert(p, tx_hand);
+        mailboxes.insert(p, mailbox.clone());
+        player_transports.push(InProcessPlayer{seat: p, rx_hand, card_sender: card_sender.clone(),
+                                               barrier: barrier.clone(), mailbox});
+    }
+    return (InProcessCoordinator{tx_hands, card_receiver, barrier, mailboxes}, player_transports);
+}
+
+impl InProcessPlayer {
+    pub fn crash_reporter(&self) -> CrashReporter {
+        return CrashReporter{event_sender: self.card_sender.clone(), barrier: self.barrier.clone()};
+    }
+}
+
+impl InProcessCoordinator {
+    pub fn barrier(&self) -> Arc<RoundBarrier> {
+        return self.barrier.clone();
+    }
+
+    fn post(&self, player_id: i32, notification: Notification) {
+        let (lock, cvar) = &**self.mailboxes.get(&player_id).unwrap();
+        *lock.lock().unwrap() = Some(notification);
+        cvar.notify_one();
+    }
+}
+
+impl CoordinatorTransport for InProcessCoordinator {
+    fn deal(&mut self, player_id: i32, hand: Vec<Card>) {
+        self.tx_hands.get(&player_id).unwrap().send(hand).unwrap();
+    }
+
+    fn notify_turn(&mut self, player_id: i32, turn: Turn) {
+        self.post(player_id, Notification::Turn(turn));
+    }
+
+    async fn receive_card(&mut self) -> PlayerEvent {
+        return self.card_receiver.recv().unwrap();
+    }
+
+    async fn receive_card_timeout(&mut self, timeout: Duration) -> Option<PlayerEvent> {
+        return self.card_receiver.recv_timeout(timeout).ok();
+    }
+
+    async fn sync_round(&mut self) {
+        self.barrier.wait(None);
+    }
+
+    fn excuse(&mut self, player_id: i32) -> bool {
+        return self.barrier.excuse(player_id);
+    }
+
+    async fn readmit(&mut self, wait: bool) -> Vec<i32> {
+        return self.barrier.readmit(wait);
+    }
+
+    fn end_game(&mut self, player_id: i32, game_over: GameOver) {
+        self.post(player_id, Notification::GameOver(game_over));
+    }
+}
+
+/* Los jugadores que todavia no volvieron siguen de largo cuando terminan lo que estaban haciendo. */
+impl Drop for InProcessCoordinator {
+    fn drop(&mut self) {
+        self.barrier.close();
+    }
+}
+
+impl PlayerTransport for InProcessPlayer {
+    async fn receive_hand(&mut self) -> std::io::Result<Vec<Card>> {
+        return self.rx_hand.recv().map_err(coordinator_left);
+    }
+
+    async fn wait_turn(&mut self) -> std::io::Result<Notification> {
+        let (lock, cvar) = &*self.mailbox;
+        let mut notification = lock.lock().unwrap();
+        while notification.is_none() {
+            notification = cvar.wait(notification).unwrap();
+        }
+        return Ok(notification.take().unwrap());
+    }
+
+    /* Si la partida termino sin esperar la carta, se pierde. */
+    fn send_card(&mut self, signed_card: SignedCard) -> std::io::Result<()> {
+        let _ = self.card_sender.send(PlayerEvent::Card(signed_card));
+        return Ok(());
+    }
+
+    async fn sync_round(&mut self) -> std::io::Result<()> {
+        self.barrier.wait(Some(self.seat));
+        return Ok(());
+    }
+
+    async fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use crate::cards::CardSuit;
+
+    #[test]
+    fn test_in_process_round_trip() {
+        let (mut coordinator, mut players) = in_process(1);
+        let mut player = players.pop().unwrap();
+        let card = Card{number: 4, suit: CardSuit::Clubs};
+        let player_thread = thread::spawn(move || block_on(async {
+            let hand = player.receive_hand().await.unwrap();
+            player.sync_round().await.unwrap();
+            let turn = player.wait_turn().await.unwrap();
+            player.send_card(SignedCard{card: hand[0], player_signature: 0}).unwrap();
+            player.sync_round().await.unwrap();
+            return turn;
+        }));
+        coordinator.deal(0, vec![card]);
+        block_on(coordinator.sync_round());
+        let table = TableView{round: 1, ..TableView::default()};
+        coordinator.notify_turn(0, Turn{can_throw_card: true, drawn_card: None, table: table.clone(), last_round: None});
+        assert_eq!(block_on(coordinator.receive_card()), PlayerEvent::Card(SignedCard{card, player_signature: 0}));
+        assert_eq!(block_on(coordinator.receive_card_timeout(Duration::from_millis(10))), None);
+        block_on(coordinator.sync_round());
+        match player_thread.join().unwrap() {
+            Notification::Turn(turn) => assert_eq!(turn.table, table),
+            Notification::GameOver(_) => panic!("Expected a turn")
+        }
+    }
+
+    #[test]
+    fn test_crashed_player_leaves_the_barrier() {
+        let (mut coordinator, mut players) = in_process(2);
+        let survivor = players.pop().unwrap();
+        let crashed = players.pop().unwrap();
+        let survivor_thread = thread::spawn(move || survivor.barrier.wait(Some(1)));
+        crashed.crash_reporter().report(0, "boom".to_string());
+        block_on(coordinator.sync_round());
+        survivor_thread.join().unwrap();
+        assert_eq!(block_on(coordinator.receive_card()), PlayerEvent::Crashed{player_id: 0, reason: "boom".to_string()});
+    }
+
+    #[test]
+    fn test_excused_player_waits_to_be_readmitted() {
+        let barrier = Arc::new(RoundBarrier::new(3));
+        assert!(barrier.excuse(1));
+        let on_time = barrier.clone();
+        let on_time_thread = thread::spawn(move || on_time.wait(Some(0)));
+        barrier.wait(None);
+        on_time_thread.join().unwrap();
+
+        let late = barrier.clone();
+        let late_thread = thread::spawn(move || {
+            late.wait(Some(1));
+            late.wait(Some(1));
+        });
+        assert_eq!(barrier.readmit(true), vec![1]);
+        let on_time = barrier.clone();
+        let on_time_thread = thread::spawn(move || on_time.wait(Some(0)));
+        barrier.wait(None);
+        on_time_thread.join().unwrap();
+        late_thread.join().unwrap();
+    }
+}